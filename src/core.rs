@@ -3,13 +3,18 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 pub mod builtins;
+pub mod compspec;
 pub mod database;
 pub mod history;
 pub mod jobtable;
+pub mod keymap;
 pub mod options;
+pub mod prompt;
 
 use crate::{error, proc_ctrl, signal};
+use self::compspec::CompSpec;
 use self::database::DataBase;
+use self::keymap::KeyMap;
 use self::options::Options;
 use std::collections::HashMap;
 use std::os::fd::{FromRawFd, OwnedFd};
@@ -44,8 +49,13 @@ pub struct ShellCore {
     pub db: DataBase,
     pub aliases: HashMap<String, String>,
     pub alias_memo: Vec<(String, String)>,
-    rewritten_history: HashMap<usize, String>,
+    history_in_file: usize,
+    history_file_read: u64, //the bytes of HISTFILE already in the history
+    history_last_search: String,
+    history_last_subst: Option<(String, String)>,
     pub history: Vec<String>,
+    history_times: Vec<Option<i64>>,
+    pub history_entry_fixed: bool,
     pub builtins: HashMap<String, fn(&mut ShellCore, &mut Vec<String>) -> i32>,
     pub sigint: Arc<AtomicBool>,
     pub read_stdin: bool,
@@ -62,14 +72,16 @@ pub struct ShellCore {
     pub job_table: Vec<JobEntry>,
     pub job_table_priority: Vec<usize>,
     current_dir: Option<path::PathBuf>, // the_current_working_directory
-    pub completion_functions: HashMap<String, String>,
-    pub default_completion_functions: String,
-    pub completion_actions: HashMap<String, (String, HashMap<String, String>)>, //command, action,
-                                                                            //options for compgen
+    pub completion_specs: HashMap<String, CompSpec>,
+    pub current_compspec: Option<CompSpec>, //modified by compopt in completion functions
+    pub keymap: KeyMap,
+    pub kill_buffer: String,
+    pub command_number: usize,
     pub measured_time: MeasuredTime,
     pub options: Options,
     pub shopts: Options,
     pub suspend_e_option: bool,
+    pub e_failure_suspended: bool, //the last failure happened while errexit was ignored
     pub script_name: String,
 }
 
@@ -82,6 +94,7 @@ impl ShellCore {
             options: Options::new_as_basic_opts(),
             shopts: Options::new_as_shopts(),
             script_name: "-".to_string(),
+            command_number: 1,
             ..Default::default()
         };
 
@@ -94,7 +107,7 @@ impl ShellCore {
         let _ = core.db.set_param("PS4", "+ ", None);
 
         if unistd::isatty(0) == Ok(true) {
-            core.db.flags += "i";
+            core.db.flags += "iH";
             core.read_stdin = false;
             let _ = core.db.set_param("PS1", "🍣 ", None);
             let _ = core.db.set_param("PS2", "> ", None);
@@ -106,6 +119,7 @@ impl ShellCore {
         let home = core.db.get_param("HOME").unwrap_or(String::new()).to_string();
         let _ = core.db.set_param("HISTFILE", &(home + "/.sush_history"), None);
         let _ = core.db.set_param("HISTFILESIZE", "2000", None);
+        let _ = core.db.set_param("HISTSIZE", "2000", None);
 
         core
     }
@@ -127,6 +141,12 @@ impl ShellCore {
         let _ = self.db.set_param("HOSTTYPE", &t_arch, None);
         let _ = self.db.set_param("OSTYPE", &t_os, None);
         let _ = self.db.set_array("BASH_VERSINFO", versinfo, None);
+        let _ = self.db.set_param("COMP_WORDBREAKS", " \t\n\"'><=;|&(:", None);
+    }
+
+    /* set -n is ignored by interactive shells */
+    pub fn noexec(&self) -> bool {
+        self.db.flags.contains('n') && ! self.db.flags.contains('i')
     }
 
     pub fn flip_exit_status(&mut self) {
@@ -158,6 +178,32 @@ impl ShellCore {
         Ok(())
     }
 
+    /* adds a frame of a function call or source to BASH_SOURCE and BASH_LINENO,
+     * and also to BASH_ARGV and BASH_ARGC under extdebug */
+    pub fn push_call_stack(&mut self, source: &str, args: &[String]) -> bool {
+        let lineno = self.db.get_param("LINENO").unwrap_or_default();
+        self.db.push_front("BASH_SOURCE", vec![source.to_string()]);
+        self.db.push_front("BASH_LINENO", vec![lineno]);
+
+        if ! self.shopts.query("extdebug") {
+            return false;
+        }
+        self.db.push_front("BASH_ARGV", args.iter().rev().cloned().collect());
+        self.db.push_front("BASH_ARGC", vec![args.len().to_string()]);
+        true
+    }
+
+    pub fn pop_call_stack(&mut self, with_args: bool) {
+        self.db.pop_front("BASH_SOURCE", 1);
+        self.db.pop_front("BASH_LINENO", 1);
+
+        if with_args {
+            let argc = self.db.get_array_elem("BASH_ARGC", "0").unwrap_or_default();
+            self.db.pop_front("BASH_ARGV", argc.parse::<usize>().unwrap_or(0));
+            self.db.pop_front("BASH_ARGC", 1);
+        }
+    }
+
     pub fn initialize_as_subshell(&mut self, pid: Pid, pgid: Pid){
         signal::restore(Signal::SIGINT);
         signal::restore(Signal::SIGTSTP);
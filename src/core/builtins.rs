@@ -3,7 +3,9 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod alias;
+mod bind;
 mod cd;
+mod fc;
 pub mod completion;
 mod getopts;
 mod history;
@@ -25,19 +27,24 @@ use crate::utils::{arg, exit, file};
 impl ShellCore {
     pub fn set_builtins(&mut self) {
         self.builtins.insert(":".to_string(), true_);
+        self.builtins.insert("_completion_loader".to_string(), completion::completion_loader);
         self.builtins.insert("alias".to_string(), alias::alias);
         self.builtins.insert("bg".to_string(), job_commands::bg);
+        self.builtins.insert("bind".to_string(), bind::bind);
         self.builtins.insert("break".to_string(), loop_control::break_);
         self.builtins.insert("builtin".to_string(), builtin);
+        self.builtins.insert("caller".to_string(), caller);
         self.builtins.insert("cd".to_string(), cd::cd);
         self.builtins.insert("command".to_string(), command);
         self.builtins.insert("compgen".to_string(), completion::compgen);
         self.builtins.insert("complete".to_string(), completion::complete);
+        self.builtins.insert("compopt".to_string(), completion::compopt);
         self.builtins.insert("continue".to_string(), loop_control::continue_);
         self.builtins.insert("declare".to_string(), parameter::declare);
         self.builtins.insert("eval".to_string(), eval);
         self.builtins.insert("exit".to_string(), exit);
         self.builtins.insert("false".to_string(), false_);
+        self.builtins.insert("fc".to_string(), fc::fc);
         self.builtins.insert("fg".to_string(), job_commands::fg);
         self.builtins.insert("getopts".to_string(), getopts::getopts);
         self.builtins.insert("history".to_string(), history::history);
@@ -73,6 +80,39 @@ pub fn builtin(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     core.builtins[&args[1]](core, &mut args[1..].to_vec())
 }
 
+pub fn caller(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let linenos = core.db.get_array_all("BASH_LINENO");
+    let sources = core.db.get_array_all("BASH_SOURCE");
+
+    if args.len() < 2 {
+        return match linenos.first() {
+            Some(lineno) => {
+                println!("{} {}", lineno, sources.get(1).map_or("NULL", |s| s.as_str()));
+                0
+            },
+            None => 1,
+        };
+    }
+
+    let n = match args[1].parse::<usize>() {
+        Ok(n) => n,
+        Err(_) => {
+            let msg = format!("caller: {}: invalid number", &args[1]);
+            error::print(&msg, core);
+            return 2;
+        },
+    };
+
+    let funcs = core.db.get_array_all("FUNCNAME");
+    match (linenos.get(n), sources.get(n+1)) {
+        (Some(lineno), Some(source)) => {
+            println!("{} {} {}", lineno, funcs.get(n+1).map_or("main", |f| f.as_str()), source);
+            0
+        },
+        _ => 1,
+    }
+}
+
 pub fn command_v(words: &mut Vec<String>, core: &mut ShellCore, large_v: bool) -> i32 {
     if words.is_empty() {
         return 0;
@@ -0,0 +1,185 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use crate::core::keymap;
+use crate::core::keymap::{Binding, FUNCTIONS};
+
+fn usage(core: &mut ShellCore, opt: &str) -> i32 {
+    let msg = format!("bind: {}: invalid option", opt);
+    error::print(&msg, core);
+    eprintln!("bind: usage: bind [-lpsvPSVX] [-m keymap] [-q name] [-u name] [-r keyseq] [-f filename] [-x keyseq:shell-command] [keyseq:readline-function or readline-command]");
+    2
+}
+
+fn quoted(seq: &str) -> String {
+    format!("\"{}\"", keymap::keyseq_to_string(seq))
+}
+
+fn print_functions(core: &mut ShellCore, readable: bool) {
+    for f in FUNCTIONS {
+        let seqs: Vec<String> = core.keymap.keyseqs_of(f).iter().map(|s| quoted(s)).collect();
+        match (readable, seqs.is_empty()) {
+            (false, true)  => println!("# {} (not bound)", f),
+            (false, false) => seqs.iter().for_each(|s| println!("{}: {}", s, f)),
+            (true, true)   => println!("{} is not bound to any keys", f),
+            (true, false)  => println!("{} can be found on {}.", f, seqs.join(", ")),
+        }
+    }
+}
+
+fn print_macros(core: &mut ShellCore, readable: bool, commands: bool) {
+    for (seq, b) in core.keymap.bindings() {
+        let body = match (b, commands) {
+            (Binding::Macro(m), false) => keymap::keyseq_to_string(m),
+            (Binding::Command(c), true) => c.replace('"', "\\\""),
+            _ => continue,
+        };
+        match readable {
+            true  => println!("{} outputs {}", keymap::keyseq_to_string(seq), body),
+            false => println!("{}: \"{}\"", quoted(seq), body),
+        }
+    }
+}
+
+fn print_variables(core: &mut ShellCore, readable: bool) {
+    for (name, value) in core.keymap.variables() {
+        match readable {
+            true  => println!("{} is set to `{}'", name, value),
+            false => println!("set {} {}", name, value),
+        }
+    }
+}
+
+fn query(core: &mut ShellCore, func: &str) -> i32 {
+    if ! FUNCTIONS.contains(&func) {
+        let msg = format!("bind: `{}': unknown function name", func);
+        error::print(&msg, core);
+        return 1;
+    }
+
+    let seqs: Vec<String> = core.keymap.keyseqs_of(func).iter().map(|s| quoted(s)).collect();
+    match seqs.is_empty() {
+        true => {
+            println!("{} is not bound to any keys.", func);
+            1
+        },
+        false => {
+            println!("{} can be invoked via {}.", func, seqs.join(", "));
+            0
+        },
+    }
+}
+
+fn read_file(core: &mut ShellCore, path: &str) -> i32 {
+    let term = core.db.get_param("TERM").unwrap_or_default();
+    match core.keymap.read_file(path, &term) {
+        Ok(errors) => {
+            for e in &errors {
+                error::print(&format!("bind: {}", e), core);
+            }
+            0
+        },
+        Err(e) => {
+            error::print(&format!("bind: {}", e), core);
+            1
+        },
+    }
+}
+
+fn with_arg<'a>(core: &mut ShellCore, args: &'a [String], pos: &mut usize, opt: char) -> Result<&'a str, i32> {
+    *pos += 1;
+    match args.get(*pos) {
+        Some(a) => Ok(a),
+        None => {
+            let msg = format!("bind: -{}: option requires an argument", opt);
+            error::print(&msg, core);
+            Err(2)
+        },
+    }
+}
+
+fn select_keymap(core: &mut ShellCore, name: &str) -> i32 {
+    match core.keymap.select(name) {
+        true  => 0,
+        false => {
+            let msg = format!("bind: `{}': invalid keymap name", name);
+            error::print(&msg, core);
+            1
+        },
+    }
+}
+
+pub fn bind(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let keymap = core.keymap.variable("keymap");
+    let mut selected = false;
+    let status = bind_in_keymap(core, args, &mut selected);
+    if selected {
+        core.keymap.select(&keymap);
+    }
+    core.sync_editing_mode();
+    status
+}
+
+fn bind_in_keymap(core: &mut ShellCore, args: &mut [String], selected: &mut bool) -> i32 {
+    let mut status = 0;
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        let opts = args[pos].clone();
+        for c in opts[1..].chars() {
+            let result = match c {
+                'l' => { FUNCTIONS.iter().for_each(|f| println!("{}", f)); Ok(0) },
+                'p' | 'P' => { print_functions(core, c == 'P'); Ok(0) },
+                's' | 'S' => { print_macros(core, c == 'S', false); Ok(0) },
+                'v' | 'V' => { print_variables(core, c == 'V'); Ok(0) },
+                'X' => { print_macros(core, false, true); Ok(0) },
+                'q' => with_arg(core, args, &mut pos, c).map(|f| query(core, f)),
+                'u' => with_arg(core, args, &mut pos, c).map(|f| {
+                    core.keymap.unbind_function(f);
+                    0
+                }),
+                'r' => with_arg(core, args, &mut pos, c).map(|s| {
+                    match keymap::parse_keyseq(s) {
+                        Ok(seq) => { core.keymap.unbind(&seq); 0 },
+                        Err(_) => 1,
+                    }
+                }),
+                'f' => with_arg(core, args, &mut pos, c).map(|f| read_file(core, f)),
+                'm' => match with_arg(core, args, &mut pos, c).map(|m| select_keymap(core, m)) {
+                    Ok(0) => { *selected = true; Ok(0) },
+                    Ok(n) | Err(n) => return n,
+                },
+                'x' => with_arg(core, args, &mut pos, c).map(|a| {
+                    match core.keymap.bind_command(a) {
+                        Ok(()) => 0,
+                        Err(e) => {
+                            error::print(&format!("bind: {}", e), core);
+                            1
+                        },
+                    }
+                }),
+                _ => return usage(core, &opts),
+            };
+
+            match result {
+                Ok(n) => status = std::cmp::max(status, n),
+                Err(n) => return n,
+            }
+        }
+        pos += 1;
+    }
+
+    for arg in &args[pos..] {
+        if let Err(e) = core.keymap.parse_line(arg) {
+            error::print(&format!("bind: {}", e), core);
+            status = 1;
+        }
+    }
+    status
+}
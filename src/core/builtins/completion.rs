@@ -1,17 +1,25 @@
 //SPDX-FileCopyrightText: 2023 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{file_check, ShellCore, Feeder};
-use crate::core::HashMap;
-use crate::elements::word::Word;
+use crate::{error, file_check, ShellCore, Feeder};
+use crate::core::builtins::source;
+use crate::core::{compspec, keymap};
+use crate::core::compspec::{single_quote, CompSpec};
+use crate::elements::command::simple::SimpleCommand;
+use crate::elements::command::Command;
+use crate::elements::io::pipe::Pipe;
+use crate::elements::subword;
+use crate::elements::word::{substitution, Word};
 use crate::utils;
-use crate::utils::{arg, directory};
+use crate::utils::{directory, glob};
 use faccess;
 use faccess::PathExt;
 use std::collections::HashSet;
+use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use nix::sys::signal::Signal;
 use rev_lines::RevLines;
 
 pub fn compgen_f(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
@@ -19,7 +27,7 @@ pub fn compgen_f(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
         2 => "".to_string(),
         _ => {
             match args[2].as_str() {
-                "--" => args[3].to_string(),
+                "--" => args.get(3).cloned().unwrap_or_default(),
                 _ => args[2].to_string(),
             }
         },
@@ -39,37 +47,16 @@ pub fn compgen_f(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
         return files.iter().map(|f| dir.clone() + &f).collect();
     }
 
-    let mut ans = directory::glob(&dir, &(key.clone() + "*"), core.shopts.query("extglob"));
+    let mut ans = directory::glob(&dir, &(key.clone() + "*"), &core.shopts);
     if key == "." {
-        ans.append(&mut directory::glob(&dir, ".", false));
-        ans.append(&mut directory::glob(&dir, "..", false));
+        ans.append(&mut directory::glob(&dir, ".", &core.shopts));
+        ans.append(&mut directory::glob(&dir, "..", &core.shopts));
     }
     ans.iter_mut().for_each(|a| { a.pop(); } );
     ans.sort();
     ans
 }
 
-fn replace_args_compgen(args: &mut Vec<String>) -> bool {
-    if args.len() < 3 || args[1] != "-A" {
-        return true;
-    }
-
-    args.remove(1);
-    let replace = match args[1].as_str() {
-        "command" => "-c",
-        "directory" => "-d",
-        "file" => "-f",
-        "user" => "-u",
-        "setopt" => "-o",
-        "stopped" => "-A stopped",
-        "job" => "-j",
-        a => a,
-    };
-
-    args[1] = replace.to_string();
-    true
-}
-
 fn command_list(target: &String, core: &mut ShellCore) -> Vec<String> {
 
     let mut comlist = HashSet::new();
@@ -98,36 +85,25 @@ pub fn compgen(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         eprintln!("sush: {}: still unsupported", &args[0]);
         return 1;
     }
-    let mut args = arg::dissolve_options(args);
-
-    replace_args_compgen(&mut args);
-
-    let ans = match args[1].as_str() {
-        "-a" => compgen_a(core, &mut args),
-        "-b" => compgen_b(core, &mut args),
-        "-c" => compgen_c(core, &mut args),
-        "-d" => compgen_d(core, &mut args),
-        "-f" => compgen_f(core, &mut args),
-        "-h" => compgen_h(core, &mut args), //history (sush original)
-        "-j" => compgen_j(core, &mut args),
-        "-u" => compgen_u(core, &mut args),
-        "-v" => compgen_v(core, &mut args),
-        "-A stopped" => compgen_stopped(core, &mut args),
-        "-W" => {
-            if args.len() < 2 {
-                eprintln!("sush: compgen: -W: option requires an argument");
-                return 2;
-            }
-            compgen_large_w(core, &mut args)
-        },
-        _ => {
-            eprintln!("sush: compgen: {}: invalid option", &args[1]);
-            return 2;
-        },
+
+    if args[1] == "-h" { //history (sush original)
+        compgen_h(core, args).iter().for_each(|a| println!("{}", &a));
+        return 0;
+    }
+
+    let (mut spec, _, words) = match parse_spec(core, args, "compgen", "") {
+        Ok(parsed) => parsed,
+        Err(status) => return status,
     };
+    let _ = spec.set_option("nosort", true);
+    let cur = words.first().cloned().unwrap_or_default();
 
+    let ans = compgen_spec(core, &spec, "", &cur, "").unwrap_or_default();
     ans.iter().for_each(|a| println!("{}", &a));
-    0
+    match ans.is_empty() {
+        true  => 1,
+        false => 0,
+    }
 }
 
 fn get_head(args: &mut Vec<String>, pos: usize) -> String {
@@ -202,7 +178,7 @@ pub fn compgen_c(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     commands
 }
 
-fn compgen_d(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+pub fn compgen_d(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     let mut paths = compgen_f(core, args);
     paths.retain(|p| file_check::is_dir(&p));
     paths
@@ -328,75 +304,472 @@ pub fn compgen_j(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     ans
 }
 
-fn opt_to_action(arg: &str) -> String {
-    match arg {
-        "-a" => "alias",
-        "-b" => "builtin",
-        "-c" => "command",
-        "-j" => "job",
-        "-o" => "setopt",
-        "-u" => "user",
-        "-v" => "variable",
-        _ => "",
-    }.to_string()
+pub fn compgen_arrayvar(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans = core.db.get_keys();
+    ans.retain(|k| core.db.is_array(k) || core.db.is_assoc(k));
+    drop_unmatch(args, 2, &mut ans);
+    ans
 }
 
-pub fn complete(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
-        return 0;
+pub fn compgen_binding(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans: Vec<String> = keymap::FUNCTIONS.iter().map(|f| f.to_string()).collect();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+pub fn compgen_e(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans: Vec<String> = env::vars().map(|(k, _)| k).collect();
+    ans.sort();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+pub fn compgen_function(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans: Vec<String> = core.db.functions.keys().cloned().collect();
+    ans.sort();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+pub fn compgen_k(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans: Vec<String> = utils::KEYWORDS.iter().map(|k| k.to_string()).collect();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+pub fn compgen_signal(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans: Vec<String> = Signal::iterator().map(|s| s.as_str().to_string()).collect();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+pub fn compgen_shopt(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans = core.shopts.get_keys();
+    ans.sort();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+/* the fields of lines in a file such as /etc/group, skipping comments */
+fn fields_in_file(path: &str, sep: Option<char>, skip: usize, take: usize) -> Vec<String> {
+    let mut ans = vec![];
+    if let Ok(f) = File::open(path) {
+        for line in BufReader::new(f).lines().map_while(Result::ok) {
+            let line = line.split('#').next().unwrap_or("");
+            let fields: Vec<&str> = match sep {
+                Some(c) => line.split(c).collect(),
+                None    => line.split_whitespace().collect(),
+            };
+            fields.iter().skip(skip).take(take).filter(|f| ! f.is_empty())
+                  .for_each(|f| ans.push(f.to_string()));
+        }
     }
+    ans
+}
+
+pub fn compgen_g(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans = fields_in_file("/etc/group", Some(':'), 0, 1);
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
 
-    let mut args = arg::dissolve_options(args);
-    //arg::replace_to_short_opt("-o", "default", "-D", &mut args);
+pub fn compgen_hostname(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let path = match core.db.get_param("HOSTFILE") {
+        Ok(f) if ! f.is_empty() => f,
+        _ => "/etc/hosts".to_string(),
+    };
+    let mut ans = fields_in_file(&path, None, 1, usize::MAX);
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
 
-    let mut options = HashMap::new();
-    let prefix = arg::consume_with_next_arg("-P", &mut args);
-    if prefix != None {
-        options.insert("-P".to_string(), prefix.unwrap().clone());
+pub fn compgen_s(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans = fields_in_file("/etc/services", None, 0, 1);
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+pub fn compgen_running(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans = vec![];
+
+    for job in &core.job_table {
+        if job.display_status == "Running" {
+            ans.push(job.text.split(" ").next().unwrap_or("").to_string());
+        }
     }
-    let suffix = arg::consume_with_next_arg("-S", &mut args);
-    if suffix != None {
-        options.insert("-S".to_string(), suffix.unwrap().clone());
+
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
+/* generates candidates for the word cur of the command com after the word prev;
+ * returns None when the completion function exits with 124 to request a retry */
+pub fn compgen_spec(core: &mut ShellCore, spec: &CompSpec, com: &str, cur: &str, prev: &str) -> Option<Vec<String>> {
+    let mut ans = vec![];
+    let mut args = vec!["".to_string(), "".to_string(), "--".to_string(), cur.to_string()];
+
+    for action in &spec.actions {
+        let mut cands = match action.as_ref() {
+            "alias" => compgen_a(core, &mut args),
+            "arrayvar" => compgen_arrayvar(core, &mut args),
+            "binding" => compgen_binding(core, &mut args),
+            "builtin" | "enabled" | "helptopic" => compgen_b(core, &mut args),
+            "command" => compgen_c(core, &mut args),
+            "directory" => compgen_d(core, &mut args),
+            "export" => compgen_e(core, &mut args),
+            "file" => compgen_f(core, &mut args),
+            "function" => compgen_function(core, &mut args),
+            "group" => compgen_g(core, &mut args),
+            "hostname" => compgen_hostname(core, &mut args),
+            "job" => compgen_j(core, &mut args),
+            "keyword" => compgen_k(core, &mut args),
+            "running" => compgen_running(core, &mut args),
+            "service" => compgen_s(core, &mut args),
+            "setopt" => compgen_o(core, &mut args),
+            "shopt" => compgen_shopt(core, &mut args),
+            "signal" => compgen_signal(core, &mut args),
+            "stopped" => compgen_stopped(core, &mut args),
+            "user" => compgen_u(core, &mut args),
+            "variable" => compgen_v(core, &mut args),
+            _ => vec![], //disabled: no builtin can be disabled
+        };
+        ans.append(&mut cands);
+    }
+
+    if let Some(glob) = &spec.glob {
+        let (dir, key) = match glob.rfind('/') {
+            Some(p) => glob.split_at(p+1),
+            None    => ("", glob.as_str()),
+        };
+        let mut paths = directory::glob(dir, key, &core.shopts);
+        paths.iter_mut().for_each(|p| { p.pop(); } );
+        ans.append(&mut paths);
     }
 
-    let action = opt_to_action(&args[1]);
-    if action != "" {
-        for command in &args[2..] {
-            core.completion_actions.insert(command.clone(), (action.clone(), options.clone()));
+    if let Some(wordlist) = &spec.wordlist {
+        let mut args = vec!["".to_string(), "".to_string(), wordlist.clone(), "--".to_string(), cur.to_string()];
+        ans.append(&mut compgen_large_w(core, &mut args));
+    }
+
+    if let Some(func) = &spec.function {
+        ans.append(&mut run_function(core, func, com, cur, prev));
+        if core.db.exit_status == 124 {
+            return None;
         }
-        return 0;
     }
 
-    if args.len() > 2 && args[1] == "-A" {
-        for a in &args[3..] {
-            core.completion_actions.insert(a.clone(), (args[2].to_string(), options.clone()));
+    if let Some(command) = &spec.command {
+        ans.append(&mut run_command(core, command, com, cur, prev));
+    }
+
+    if let Some(filter) = &spec.filter {
+        let (negate, pattern) = match filter.strip_prefix('!') {
+            Some(p) => (true, p),
+            None    => (false, filter.as_str()),
+        };
+        let pattern = pattern.replace("\\&", "\0").replace("&", cur).replace("\0", "&");
+        let extglob = core.shopts.query("extglob");
+        ans.retain(|c| glob::parse_and_compare(c, &pattern, extglob) == negate);
+    }
+
+    if ! spec.has("nosort") {
+        ans.sort();
+        ans.dedup();
+    }
+    Some(ans.iter().map(|c| spec.prefix.clone() + c + &spec.suffix).collect())
+}
+
+fn run_function(core: &mut ShellCore, func: &str, com: &str, cur: &str, prev: &str) -> Vec<String> {
+    let _ = core.db.set_array("COMPREPLY", vec![], None);
+    let command = format!("{} {} {} {}", func, single_quote(com), single_quote(cur), single_quote(prev));
+    let mut feeder = Feeder::new(&command);
+    if let Ok(Some(mut c)) = SimpleCommand::parse(&mut feeder, core) {
+        let mut dummy = Pipe::new("".to_string());
+        let _ = c.exec(core, &mut dummy);
+    }
+    core.db.get_array_all("COMPREPLY")
+}
+
+/* runs the command of complete -C with the arguments and takes the lines of its output */
+fn run_command(core: &mut ShellCore, command: &str, com: &str, cur: &str, prev: &str) -> Vec<String> {
+    let text = format!("$({} {} {} {})", command, single_quote(com), single_quote(cur), single_quote(prev));
+    let mut feeder = Feeder::new(&text);
+    let mut word = match subword::parse(&mut feeder, core) {
+        Ok(Some(sw)) => Word::from(sw),
+        _ => return vec![],
+    };
+    match substitution::eval(&mut word, core) {
+        Ok(()) => {
+            let output: String = word.subwords.iter().map(|s| s.get_text()).collect();
+            output.lines().map(|l| l.to_string()).collect()
+        },
+        Err(_) => vec![],
+    }
+}
+
+fn completion_dirs(core: &mut ShellCore) -> Vec<String> {
+    let home = core.db.get_param("HOME").unwrap_or_default();
+    let user_dir = match core.db.get_param("BASH_COMPLETION_USER_DIR") {
+        Ok(d) if ! d.is_empty() => d,
+        _ => match core.db.get_param("XDG_DATA_HOME") {
+            Ok(d) if ! d.is_empty() => d + "/bash-completion",
+            _ => home + "/.local/share/bash-completion",
+        },
+    };
+
+    let data_dirs = match core.db.get_param("XDG_DATA_DIRS") {
+        Ok(d) if ! d.is_empty() => d,
+        _ => "/usr/local/share:/usr/share".to_string(),
+    };
+
+    let mut ans = vec![user_dir + "/completions"];
+    for dir in data_dirs.split(':').filter(|d| ! d.is_empty()) {
+        ans.push(dir.to_string() + "/bash-completion/completions");
+    }
+    ans
+}
+
+/* sources the completion file for the command and returns 124 to retry the completion */
+pub fn completion_loader(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() < 2 {
+        return 1;
+    }
+    let com = args[1].rsplit('/').next().unwrap_or("").to_string();
+    if com.is_empty() || com.starts_with('.') {
+        return 1;
+    }
+
+    for dir in completion_dirs(core) {
+        for name in [com.clone(), com.clone() + ".bash", "_".to_string() + &com] {
+            let path = dir.clone() + "/" + &name;
+            if ! file_check::is_regular_file(&path) {
+                continue;
+            }
+
+            let mut source_args = vec!["source".to_string(), path];
+            source::source(core, &mut source_args);
+            if core.completion_specs.contains_key(&com) {
+                return 124;
+            }
         }
+    }
+    1
+}
 
-        return 0;
+fn takes_arg(opt: char) -> bool {
+    "oAGWFCXPS".contains(opt)
+}
+
+fn set_spec_option(spec: &mut CompSpec, opt: char, arg: String) -> Result<(), String> {
+    match opt {
+        'o' => spec.set_option(&arg, true)?,
+        'A' => spec.add_action(&arg)?,
+        'G' => spec.glob = Some(arg),
+        'W' => spec.wordlist = Some(arg),
+        'F' => spec.function = Some(arg),
+        'C' => spec.command = Some(arg),
+        'X' => spec.filter = Some(arg),
+        'P' => spec.prefix = arg,
+        'S' => spec.suffix = arg,
+        c => match compspec::action_of(c) {
+            Some(a) => spec.add_action(a)?,
+            None => return Err(format!("-{}: invalid option", c)),
+        },
     }
-    //complete -F _comp_cmd_gpg2 -o default gpg2
+    Ok(())
+}
 
-    // completion functions
-    if args.len() > 3 && args[1] == "-D" && args[2] == "-F" {
-        core.default_completion_functions = args[3].clone();
-        return 0;
+fn print_specs(core: &mut ShellCore, names: &[String], com: &str) -> i32 {
+    let mut names = names.to_vec();
+    if names.is_empty() {
+        names = core.completion_specs.keys().cloned().collect();
+        names.sort();
+    }
+
+    let mut status = 0;
+    for name in &names {
+        match core.completion_specs.get(name) {
+            Some(spec) => println!("{}", spec.print_form(name)),
+            None => {
+                error::print(&format!("{}: {}: no completion specification", com, name), core);
+                status = 1;
+            },
+        }
     }
+    status
+}
+
+fn spec_name(opt: char) -> Option<&'static str> {
+    match opt {
+        'D' => Some(compspec::DEFAULT),
+        'E' => Some(compspec::EMPTY),
+        'I' => Some(compspec::INITIAL),
+        _ => None,
+    }
+}
 
-    if args.len() > 3 && args[1] == "-F" {
-        let func_name = args[2].clone();
+/* parses the options of complete and compgen; the flags in extra are returned
+ * separately from the spec with the rest of the arguments */
+fn parse_spec(core: &mut ShellCore, args: &[String], com: &str, extra: &str)
+    -> Result<(CompSpec, Vec<char>, Vec<String>), i32> {
+    let mut spec = CompSpec::default();
+    let mut flags = vec![];
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        let opts: Vec<char> = args[pos][1..].chars().collect();
+        for (i, c) in opts.iter().enumerate() {
+            if extra.contains(*c) {
+                flags.push(*c);
+                continue;
+            }
+
+            let arg = match takes_arg(*c) {
+                true if i + 1 < opts.len() => opts[i+1..].iter().collect(),
+                true => {
+                    pos += 1;
+                    match args.get(pos) {
+                        Some(a) => a.clone(),
+                        None => {
+                            let msg = format!("{}: -{}: option requires an argument", com, c);
+                            error::print(&msg, core);
+                            return Err(2);
+                        },
+                    }
+                },
+                false => String::new(),
+            };
 
-        if args.len() > 5 && args[3] == "-o" && args[4] == "default" {
-            for a in &mut args[5..] {
-                core.completion_functions.insert(a.clone(), func_name.clone());
+            if let Err(e) = set_spec_option(&mut spec, *c, arg) {
+                error::print(&format!("{}: {}", com, e), core);
+                return Err(2);
             }
+            if takes_arg(*c) {
+                break;
+            }
+        }
+        pos += 1;
+    }
+    Ok((spec, flags, args[pos..].to_vec()))
+}
+
+pub fn complete(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (spec, flags, operands) = match parse_spec(core, args, "complete", "prDEI") {
+        Ok(parsed) => parsed,
+        Err(status) => return status,
+    };
+    let (print, remove) = (flags.contains(&'p'), flags.contains(&'r'));
+    let mut names: Vec<String> = flags.iter().filter_map(|c| spec_name(*c))
+                                      .map(|n| n.to_string()).collect();
+    names.extend(operands);
+
+    if remove {
+        if names.is_empty() {
+            core.completion_specs.clear();
             return 0;
         }
-        for a in &mut args[3..] {
-            core.completion_functions.insert(a.clone(), func_name.clone());
+        let mut status = 0;
+        for name in &names {
+            if core.completion_specs.remove(name).is_none() {
+                error::print(&format!("complete: {}: no completion specification", name), core);
+                status = 1;
+            }
         }
+        return status;
+    }
+
+    if print || names.is_empty() {
+        return print_specs(core, &names, "complete");
+    }
+
+    for name in names {
+        core.completion_specs.insert(name, spec.clone());
+    }
+    0
+}
+
+pub fn compopt(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut changes = vec![];
+    let mut names = vec![];
+    let mut pos = 1;
+
+    while pos < args.len() && (args[pos].starts_with("-") || args[pos].starts_with("+"))
+    && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+
+        let on = args[pos].starts_with("-");
+        for c in args[pos][1..].chars() {
+            match c {
+                'o' => {
+                    pos += 1;
+                    match args.get(pos) {
+                        Some(a) => changes.push((a.clone(), on)),
+                        None => {
+                            error::print("compopt: -o: option requires an argument", core);
+                            return 2;
+                        },
+                    }
+                },
+                c if spec_name(c).is_some() => names.push(spec_name(c).unwrap().to_string()),
+                c => {
+                    error::print(&format!("compopt: -{}: invalid option", c), core);
+                    return 2;
+                },
+            }
+        }
+        pos += 1;
+    }
+    names.extend(args[pos..].iter().cloned());
+
+    if names.is_empty() {
+        let mut spec = match core.current_compspec.take() {
+            Some(s) => s,
+            None => {
+                error::print("compopt: not currently executing completion function", core);
+                return 1;
+            },
+        };
+        let status = change_options(core, &mut spec, &changes, "");
+        core.current_compspec = Some(spec);
+        return status;
+    }
+
+    let mut status = 0;
+    for name in &names {
+        let mut spec = match core.completion_specs.get(name) {
+            Some(s) => s.clone(),
+            None => {
+                error::print(&format!("compopt: {}: no completion specification", name), core);
+                status = 1;
+                continue;
+            },
+        };
+        status = std::cmp::max(status, change_options(core, &mut spec, &changes, name));
+        core.completion_specs.insert(name.clone(), spec);
+    }
+    status
+}
+
+fn change_options(core: &mut ShellCore, spec: &mut CompSpec,
+                  changes: &[(String, bool)], name: &str) -> i32 {
+    if changes.is_empty() {
+        println!("{}", spec.compopt_form(name));
         return 0;
     }
 
-    eprintln!("sush: {} {}: still unsupported", &args[0], &args[1]);
-    1
+    for (opt, on) in changes {
+        if let Err(e) = spec.set_option(opt, *on) {
+            error::print(&format!("compopt: {}", e), core);
+            return 2;
+        }
+    }
+    0
 }
@@ -0,0 +1,243 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, Feeder, Script, ShellCore};
+use crate::utils::file;
+
+#[derive(Default)]
+struct FcOptions {
+    list: bool,
+    no_number: bool,
+    reverse: bool,
+    substitute: bool,
+    editor: Option<String>,
+    operands: Vec<String>,
+}
+
+fn parse_options(core: &mut ShellCore, args: &[String]) -> Result<FcOptions, i32> {
+    let mut opts = FcOptions::default();
+    let mut pos = 1;
+
+    while pos < args.len() {
+        let arg = &args[pos];
+        if arg == "--" {
+            pos += 1;
+            break;
+        }
+        if ! arg.starts_with("-") || arg.len() == 1
+        || arg[1..].chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+
+        for c in arg[1..].chars() {
+            match c {
+                'l' => opts.list = true,
+                'n' => opts.no_number = true,
+                'r' => opts.reverse = true,
+                's' => opts.substitute = true,
+                'e' => {
+                    if pos + 1 >= args.len() {
+                        error::print("fc: -e: option requires an argument", core);
+                        return Err(2);
+                    }
+                    pos += 1;
+                    opts.editor = Some(args[pos].clone());
+                },
+                _ => {
+                    let msg = format!("fc: -{}: invalid option", c);
+                    error::print(&msg, core);
+                    eprintln!("fc: usage: fc [-e ename] [-lnr] [first] [last] or fc -s [pat=rep] [command]");
+                    return Err(2);
+                },
+            }
+        }
+        pos += 1;
+    }
+
+    if opts.editor.as_deref() == Some("-") {
+        opts.substitute = true;
+    }
+    opts.operands = args[pos..].to_vec();
+    Ok(opts)
+}
+
+/* the index of the oldest entry that fc can refer to and the number of the newest one */
+fn range(core: &ShellCore) -> (usize, usize) {
+    let skip = match core.read_stdin || core.history.is_empty() {
+        true  => 0,
+        false => 1,
+    };
+    (skip, core.history.len() - skip)
+}
+
+fn number_of(core: &ShellCore, spec: &str, clamp: bool) -> Option<usize> {
+    let (skip, newest) = range(core);
+    if newest == 0 {
+        return None;
+    }
+
+    if let Ok(n) = spec.parse::<isize>() {
+        let num = match n < 0 {
+            true  => newest as isize + n + 1,
+            false => n,
+        };
+
+        return match (num >= 1 && num <= newest as isize, clamp) {
+            (true, _)  => Some(num as usize),
+            (false, true) => Some(std::cmp::min(std::cmp::max(num, 1), newest as isize) as usize),
+            (false, false) => None,
+        };
+    }
+
+    let len = core.history.len();
+    core.history[skip..].iter().position(|h| h.starts_with(spec))
+                        .map(|i| len - skip - i)
+}
+
+fn entry(core: &ShellCore, num: usize) -> String {
+    let len = core.history.len();
+    core.history[len - num].replace("↵ \0", "\n")
+}
+
+fn select(core: &mut ShellCore, opts: &FcOptions, first_default: &str) -> Result<Vec<usize>, i32> {
+    let first = opts.operands.first().map(|s| s.as_str()).unwrap_or(first_default);
+    let last = match opts.operands.get(1) {
+        Some(s) => s.as_str(),
+        None => if opts.list { "-1" }else{ first },
+    };
+
+    let (from, to) = match (number_of(core, first, opts.list), number_of(core, last, opts.list)) {
+        (Some(f), Some(t)) => (f, t),
+        _ => {
+            error::print("fc: history specification out of range", core);
+            return Err(1);
+        },
+    };
+
+    let mut nums: Vec<usize> = match from <= to {
+        true  => (from..=to).collect(),
+        false => (to..=from).rev().collect(),
+    };
+    if opts.reverse {
+        nums.reverse();
+    }
+    Ok(nums)
+}
+
+fn run(core: &mut ShellCore, command: &str) -> i32 {
+    if ! core.read_stdin && ! core.history.is_empty() {
+        core.history[0] = command.trim_end().replace("\n", "↵ \0");
+        core.history_entry_fixed = true;
+    }
+
+    let mut feeder = Feeder::new(command);
+    match Script::parse(&mut feeder, core, false) {
+        Ok(Some(mut s)) => {let _ = s.exec(core); },
+        Err(e) => e.print(core),
+        _ => {},
+    }
+    core.db.exit_status
+}
+
+fn substitute_and_run(core: &mut ShellCore, opts: &FcOptions) -> i32 {
+    let mut subs = vec![];
+    let mut spec = "-1".to_string();
+    for op in &opts.operands {
+        match op.split_once('=') {
+            Some((old, new)) => subs.push((old.to_string(), new.to_string())),
+            None => spec = op.clone(),
+        }
+    }
+
+    let num = match number_of(core, &spec, false) {
+        Some(n) => n,
+        None => {
+            error::print("fc: no command found", core);
+            return 1;
+        },
+    };
+
+    let mut command = entry(core, num);
+    for (old, new) in subs {
+        if ! old.is_empty() {
+            command = command.replace(&old, &new);
+        }
+    }
+
+    println!("{}", &command);
+    run(core, &(command + "\n"))
+}
+
+fn list(core: &mut ShellCore, opts: &FcOptions) -> i32 {
+    let nums = match select(core, opts, "-16") {
+        Ok(v) => v,
+        Err(n) => return n,
+    };
+
+    for n in nums {
+        match opts.no_number {
+            true  => println!("\t {}", entry(core, n)),
+            false => println!("{}\t {}", n, entry(core, n)),
+        }
+    }
+    0
+}
+
+fn editor_command(core: &mut ShellCore, opts: &FcOptions) -> String {
+    match &opts.editor {
+        Some(e) => e.clone(),
+        None    => file::editor(core, &["FCEDIT", "EDITOR"]),
+    }
+}
+
+fn edit_and_run(core: &mut ShellCore, opts: &FcOptions) -> i32 {
+    let nums = match select(core, opts, "-1") {
+        Ok(v) => v,
+        Err(n) => return n,
+    };
+
+    let text: String = nums.into_iter().map(|n| entry(core, n) + "\n").collect();
+    let editor = editor_command(core, opts);
+    let (commands, status) = match file::edit_text(core, &editor, &text, run_editor) {
+        Ok(r) => r,
+        Err(e) => {
+            error::print(&format!("fc: {}", e), core);
+            return 1;
+        },
+    };
+
+    if status != 0 {
+        return status;
+    }
+
+    eprint!("{}", &commands);
+    run(core, &commands)
+}
+
+fn run_editor(core: &mut ShellCore, command: &str) -> i32 {
+    let mut feeder = Feeder::new(&format!("{}\n", command));
+    match Script::parse(&mut feeder, core, false) {
+        Ok(Some(mut s)) => {let _ = s.exec(core); },
+        Err(e) => {
+            e.print(core);
+            core.db.exit_status = 1;
+        },
+        _ => {},
+    }
+    core.db.exit_status
+}
+
+pub fn fc(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let opts = match parse_options(core, args) {
+        Ok(o) => o,
+        Err(n) => return n,
+    };
+
+    if opts.substitute {
+        substitute_and_run(core, &opts)
+    }else if opts.list {
+        list(core, &opts)
+    }else{
+        edit_and_run(core, &opts)
+    }
+}
@@ -1,33 +1,177 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::{error, ShellCore};
+use crate::core::history::expansion::HistoryExpansion;
+use crate::utils::clock;
 
-pub fn history(core: &mut ShellCore, _: &mut Vec<String>) -> i32 {
-    let mut number = 1;
+fn usage(core: &mut ShellCore, opt: &str) -> i32 {
+    let msg = format!("history: {}: invalid option", opt);
+    error::print(&msg, core);
+    eprintln!("history: usage: history [-c] [-d offset] [n] or history -anrw [filename] or history -ps arg [arg...]");
+    2
+}
+
+fn print_entries(core: &mut ShellCore, num: usize) {
+    let format = match core.db.has_value("HISTTIMEFORMAT") {
+        true  => Some(core.db.get_param("HISTTIMEFORMAT").unwrap_or_default()),
+        false => None,
+    };
+
+    let len = core.history.len();
+    for i in (0..std::cmp::min(num, len)).rev() {
+        let time = match (&format, core.history_time(i)) {
+            (Some(f), Some(t)) => clock::strftime(f, t),
+            (Some(_), None)    => "??".to_string(),
+            (None, _)          => String::new(),
+        };
+        println!("{:5} {}{}", len - i, time, &core.history[i]);
+    }
+}
+
+fn offset_to_index(core: &mut ShellCore, offset: &str) -> Option<usize> {
+    let len = core.history.len() as isize;
+    match offset.parse::<isize>() {
+        Ok(n) if n > 0 && n <= len  => Some((len - n) as usize),
+        Ok(n) if n < 0 && -n <= len => Some((-n - 1) as usize),
+        _ => None,
+    }
+}
+
+fn delete(core: &mut ShellCore, offset: &str) -> i32 {
+    let sep = offset.char_indices().skip(1).find(|(_, c)| *c == '-').map(|(i, _)| i);
+    let (from, to) = match sep {
+        Some(p) => (&offset[..p], &offset[p+1..]),
+        None    => (offset, offset),
+    };
+
+    match (offset_to_index(core, from), offset_to_index(core, to)) {
+        (Some(start), Some(end)) if end <= start => {
+            for i in (end..=start).rev() {
+                core.delete_history(i);
+            }
+            0
+        },
+        _ => {
+            let msg = format!("history: {}: history position out of range", offset);
+            error::print(&msg, core);
+            1
+        },
+    }
+}
+
+fn print_expansion(core: &mut ShellCore, args: &[String]) -> i32 {
+    for a in args {
+        match core.expand_history(a) {
+            Ok(HistoryExpansion::Unchanged) => println!("{}", a),
+            Ok(HistoryExpansion::Expanded(s)) |
+            Ok(HistoryExpansion::PrintOnly(s)) => println!("{}", s),
+            Err(e) => {
+                e.print(core);
+                return 1;
+            },
+        }
+    }
+    0
+}
 
-    let filename = core.db.get_param("HISTFILE").unwrap_or(String::new());
-    if filename == "" {
+fn store(core: &mut ShellCore, args: &[String]) -> i32 {
+    if args.is_empty() {
         return 0;
     }
 
-    let file = match File::open(&filename) {
-        Ok(f) => f,
-        _     => return 0,
+    let entry = args.join(" ");
+    match core.read_stdin || core.history.is_empty() {
+        true  => core.insert_history(0, entry),
+        false => {
+            core.history[0] = entry;
+            core.history_entry_fixed = true;
+        },
+    }
+    core.limit_history_size();
+    0
+}
+
+fn file_operation(core: &mut ShellCore, opt: char, filename: Option<&str>) -> i32 {
+    let ok = match opt {
+        'a' => core.append_history_to_file(filename),
+        'w' => core.overwrite_history_file(filename),
+        'n' => { core.read_history_file(filename, true); true },
+        _   => { core.read_history_file(filename, false); true },
     };
 
-    let f = BufReader::new(file);
-    for line in f.lines() {
-        println!("{:5} {}", number, &line.unwrap());
-        number += 1;
+    match ok {
+        true  => 0,
+        false => 1,
     }
+}
 
-    for h in core.history.iter().rev() {
-        println!("{:5} {}", number, &h);
-        number += 1;
+pub fn history(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut clear = false;
+    let mut delete_offset = None;
+    let mut mode = None;
+    let mut pos = 1;
+
+    while pos < args.len() && args[pos].starts_with("-") && args[pos].len() > 1 {
+        if args[pos] == "--" {
+            pos += 1;
+            break;
+        }
+        if args[pos][1..].chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+
+        for c in args[pos][1..].chars() {
+            match c {
+                'c' => clear = true,
+                'd' => {
+                    if pos + 1 >= args.len() {
+                        error::print("history: -d: option requires an argument", core);
+                        return 2;
+                    }
+                    pos += 1;
+                    delete_offset = Some(args[pos].clone());
+                },
+                'a' | 'n' | 'r' | 'w' | 'p' | 's' => mode = Some(c),
+                _ => return usage(core, &args[pos]),
+            }
+        }
+        pos += 1;
     }
 
+    if clear {
+        core.clear_history();
+        return 0;
+    }
+    if let Some(offset) = delete_offset {
+        return delete(core, &offset);
+    }
+
+    let rest = &args[pos..];
+    match mode {
+        Some('p') => return print_expansion(core, rest),
+        Some('s') => return store(core, rest),
+        Some(c)   => return file_operation(core, c, rest.first().map(|s| s.as_str())),
+        None      => {},
+    }
+
+    if rest.len() > 1 {
+        error::print("history: too many arguments", core);
+        return 1;
+    }
+
+    let num = match rest.first() {
+        None => core.history.len(),
+        Some(s) => match s.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => {
+                let msg = format!("history: {}: numeric argument required", s);
+                error::print(&msg, core);
+                return 1;
+            },
+        },
+    };
+
+    print_entries(core, num);
     0
 }
@@ -2,16 +2,23 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{error, ShellCore};
+use crate::core::options;
 use crate::error::exec::ExecError;
 use crate::utils::arg;
 use super::parameter;
 
+/* the order of the flags in $- */
+const FLAG_ORDER: &str = "abefhikmnptuvxBCEHPT";
+
 fn set_option(core: &mut ShellCore, opt: char, pm: char) {
     if pm == '+' {
         core.db.flags.retain(|e| e != opt);
     }else{
         if ! core.db.flags.contains(opt) {
             core.db.flags.push(opt);
+            let mut flags = core.db.flags.chars().collect::<Vec<char>>();
+            flags.sort_by_key(|f| FLAG_ORDER.find(*f).unwrap_or(FLAG_ORDER.len()));
+            core.db.flags = flags.into_iter().collect();
         }
     }
 }
@@ -35,7 +42,7 @@ pub fn set_options(core: &mut ShellCore, args: &[String]) -> Result<(), ExecErro
             error::internal("not an option");
             return 1;
             */
-        }else if "xveB".find(ch).is_none() {
+        }else if options::flag_of_char(ch).is_none() {
             return Err(ExecError::InvalidOption(a.to_string()));
             /*
             eprintln!("sush: set: {}: invalid option", &a);
@@ -48,8 +55,38 @@ pub fn set_options(core: &mut ShellCore, args: &[String]) -> Result<(), ExecErro
     Ok(())
 }
 
+fn set_long_option(core: &mut ShellCore, name: &str, positive: bool) -> Result<(), ExecError> {
+    if let Some(ch) = options::flag_of(name) {
+        set_option(core, ch, if positive {'-'} else {'+'});
+        return Ok(());
+    }
+
+    if name == "vi" || name == "emacs" {
+        let mode = match (name, positive) {
+            ("vi", true) | ("emacs", false) => "vi",
+            _ => "emacs",
+        };
+        core.keymap.set_editing_mode(mode);
+        core.sync_editing_mode();
+        return Ok(());
+    }
+
+    match core.options.exist(name) {
+        true  => {core.options.set(name, positive);},
+        false => return Err(ExecError::Other(format!("set: {}: invalid option name", name))),
+    }
+    Ok(())
+}
+
+pub fn query_option(core: &ShellCore, name: &str) -> bool {
+    match options::flag_of(name) {
+        Some(ch) => core.db.flags.contains(ch),
+        None     => core.options.query(name),
+    }
+}
+
 pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    let mut args = arg::dissolve_options(args);
+    let args = arg::dissolve_options(args);
 
     if args.is_empty() {
         panic!("never come here");
@@ -57,44 +94,46 @@ pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         return parameter::print_all(core);
     }
 
-    if args[1].starts_with("--") {
-        args.remove(0);
-        return match parameter::set_positions(core, &args) {
-            Ok(()) => 0,
-            Err(e) => {
-                e.print(core);
-                return 1;
-            },
+    let mut pos = 1;
+    while pos < args.len() {
+        let opt = args[pos].as_str();
+        if opt == "--" {
+            pos += 1;
+            break;
         }
-    }
-
-    if args[1] == "-o" || args[1] == "+o" {
-        let positive = args[1] == "-o";
 
-        if args.len() == 2 {
-            core.options.print_all(positive);
-            return 0;
-        }else{
-            if args[2] == "noglob" {
-                eprintln!("{}: not supprted yet", &args[2]);
-                return 1;
+        let result = if opt == "-o" || opt == "+o" {
+            let positive = opt == "-o";
+            pos += 1;
+            match args.get(pos) {
+                Some(name) => set_long_option(core, name, positive),
+                None => {
+                    core.options.with_flags(&core.db.flags).print_all(positive);
+                    return 0;
+                },
             }
-            return match core.options.set(&args[2], positive) {
-                true  => 0,
-                false => 2,
-            };
-        }
-    }
+        }else if opt.starts_with("-") || opt.starts_with("+") {
+            set_options(core, &args[pos..pos+1])
+        }else{
+            break;
+        };
 
-    match args[1].starts_with("-") || args[1].starts_with("+") {
-        true  => if let Err(e) = set_options(core, &args[1..]) {
+        if let Err(e) = result {
             e.print(core);
             return 2;
-        },
-        false => if let Err(e) = parameter::set_positions(core, &args) {
-            e.print(core);
-            return 2;
-        },
+        }
+        pos += 1;
+    }
+
+    if pos == args.len() && args[pos-1] != "--" {
+        return 0;
+    }
+
+    let mut positions = vec![core.db.get_param("0").unwrap_or_default()];
+    positions.extend(args[pos..].iter().cloned());
+    if let Err(e) = parameter::set_positions(core, &positions) {
+        e.print(core);
+        return 2;
     }
     0
 }
@@ -167,7 +206,9 @@ pub fn shopt(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 
     let res = match args[1].as_str() {
         "-s" => {
-            if ["extglob", "progcomp"].iter().any(|&e| e == args[2]) {
+            if ["dotglob", "extdebug", "extglob", "failglob", "globstar", "inherit_errexit",
+                "nocaseglob", "nocasematch", "nullglob", "progcomp", "histreedit", "histverify",
+                "promptvars", "syntax_highlight", "autosuggest"].iter().any(|&e| e == args[2]) {
                 core.shopts.set(&args[2], true)
             }else{
                 let msg = format!("shopt: {}: not supported yet", &args[2]);
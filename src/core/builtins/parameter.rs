@@ -29,7 +29,7 @@ fn set_local(arg: &str, core: &mut ShellCore, layer: usize) -> Result<(), ExecEr
     let mut feeder = Feeder::new(arg);
     if feeder.scanner_name(core) == feeder.len() { // name only
         let name = feeder.consume(feeder.len());
-        return core.db.set_param(&name, "", Some(layer));
+        return core.db.set_unassigned_param(&name, Some(layer));
     }
 
     match Substitution::parse(&mut feeder, core) {
@@ -2,9 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{file_check, Script, ShellCore, Feeder};
-use crate::elements::io;
 use std::fs::File;
-use std::os::fd::IntoRawFd;
 
 pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if args.len() < 2 {
@@ -18,23 +16,21 @@ pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         return 1;
     }
 
-    let file = match File::open(&args[1]) {
-        Ok(f)  => f, 
-        Err(e) => {
-            eprintln!("sush: {}: {}", &args[1], &e);
-            return 1;
-        }, 
-    };
-
-    let fd = file.into_raw_fd();
-    let backup = io::backup(0);
-    io::replace(fd, 0);
-    let read_stdin_backup = core.read_stdin;
-    core.read_stdin = true;
+    if let Err(e) = File::open(&args[1]) {
+        eprintln!("sush: {}: {}", &args[1], &e);
+        return 1;
+    }
+
     core.source_function_level += 1;
     core.source_level += 1;
+    let in_function = core.db.len("FUNCNAME") > 0;
+    if in_function {
+        core.db.push_front("FUNCNAME", vec!["source".to_string()]);
+    }
+    let with_args = core.push_call_stack(&args[1], &args[2..]);
 
     let mut feeder = Feeder::new("");
+    feeder.set_file(&args[1]);
     loop {
         match feeder.feed_line(core) {
             Ok(()) => {}, 
@@ -52,10 +48,12 @@ pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         }
     }
 
-    io::replace(backup, 0);
+    if in_function {
+        core.db.pop_front("FUNCNAME", 1);
+    }
+    core.pop_call_stack(with_args);
     core.source_function_level -= 1;
     core.source_level -= 1;
     core.return_flag = false;
-    core.read_stdin = read_stdin_backup;
     core.db.exit_status
 }
@@ -0,0 +1,122 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::BTreeSet;
+
+/* keys of the specs for complete -D, -E and -I */
+pub const DEFAULT: &str = "-D";
+pub const EMPTY: &str = "-E";
+pub const INITIAL: &str = "-I";
+
+pub const OPTIONS: &[&str] = &[
+    "bashdefault", "default", "dirnames", "filenames", "fullquote",
+    "noquote", "nosort", "nospace", "plusdirs",
+];
+
+/* actions of complete -A and their short options */
+pub const ACTIONS: &[(&str, Option<char>)] = &[
+    ("alias", Some('a')), ("arrayvar", None), ("binding", None),
+    ("builtin", Some('b')), ("command", Some('c')), ("directory", Some('d')),
+    ("disabled", None), ("enabled", None), ("export", Some('e')),
+    ("file", Some('f')), ("function", None), ("group", Some('g')),
+    ("helptopic", None), ("hostname", None), ("job", Some('j')),
+    ("keyword", Some('k')), ("running", None), ("service", Some('s')),
+    ("setopt", None), ("shopt", None), ("signal", None),
+    ("stopped", None), ("user", Some('u')), ("variable", Some('v')),
+];
+
+pub fn action_of(opt: char) -> Option<&'static str> {
+    ACTIONS.iter().find(|(_, c)| *c == Some(opt)).map(|(a, _)| *a)
+}
+
+pub fn single_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompSpec {
+    pub actions: BTreeSet<String>,
+    pub options: BTreeSet<String>,
+    pub function: Option<String>,
+    pub command: Option<String>,
+    pub glob: Option<String>,
+    pub wordlist: Option<String>,
+    pub filter: Option<String>,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl CompSpec {
+    pub fn has(&self, option: &str) -> bool {
+        self.options.contains(option)
+    }
+
+    pub fn set_option(&mut self, option: &str, on: bool) -> Result<(), String> {
+        if ! OPTIONS.contains(&option) {
+            return Err(format!("{}: invalid option name", option));
+        }
+        match on {
+            true  => self.options.insert(option.to_string()),
+            false => self.options.remove(option),
+        };
+        Ok(())
+    }
+
+    pub fn add_action(&mut self, action: &str) -> Result<(), String> {
+        if ! ACTIONS.iter().any(|(a, _)| *a == action) {
+            return Err(format!("{}: invalid action name", action));
+        }
+        self.actions.insert(action.to_string());
+        Ok(())
+    }
+
+    /* whether candidates are treated as file names */
+    pub fn filenames(&self) -> bool {
+        self.has("filenames") || self.actions.contains("file") || self.actions.contains("directory")
+    }
+
+    /* the form of complete -p */
+    pub fn print_form(&self, name: &str) -> String {
+        let mut ans = "complete ".to_string();
+        for opt in &self.options {
+            ans += &format!("-o {} ", opt);
+        }
+        for (action, _) in ACTIONS.iter().filter(|(a, c)| c.is_some() && self.actions.contains(*a)) {
+            ans += &format!("-{} ", action_short(action));
+        }
+        for (action, _) in ACTIONS.iter().filter(|(a, c)| c.is_none() && self.actions.contains(*a)) {
+            ans += &format!("-A {} ", action);
+        }
+
+        let quoted = [("-G", &self.glob), ("-W", &self.wordlist),
+                      ("-P", &Some(self.prefix.clone()).filter(|p| ! p.is_empty())),
+                      ("-S", &Some(self.suffix.clone()).filter(|s| ! s.is_empty())),
+                      ("-X", &self.filter), ("-C", &self.command)];
+        for (opt, arg) in quoted {
+            if let Some(a) = arg {
+                ans += &format!("{} {} ", opt, single_quote(a));
+            }
+        }
+        if let Some(f) = &self.function {
+            ans += &format!("-F {} ", f);
+        }
+        ans + name
+    }
+
+    /* the form of compopt without option arguments */
+    pub fn compopt_form(&self, name: &str) -> String {
+        let mut ans = "compopt".to_string();
+        for opt in OPTIONS {
+            let sign = if self.has(opt) { "-" } else { "+" };
+            ans += &format!(" {}o {}", sign, opt);
+        }
+        match name.is_empty() {
+            true  => ans,
+            false => ans + " " + name,
+        }
+    }
+}
+
+fn action_short(action: &str) -> char {
+    ACTIONS.iter().find(|(a, _)| *a == action).and_then(|(_, c)| *c).unwrap_or(' ')
+}
@@ -96,13 +96,32 @@ impl DataBase {
     pub fn has_value(&mut self, name: &str) -> bool {
         let num = self.params.len();
         for layer in (0..num).rev()  {
-            if let Some(_) = self.params[layer].get(name) {
-                return true;
+            if let Some(d) = self.params[layer].get(name) {
+                return d.is_assigned();
             }
         }
         false
     }
 
+    /* for set -u; the special parameters except unassigned positional ones are always set */
+    pub fn is_unset(&mut self, name: &str) -> bool {
+        if let Ok(n) = name.parse::<usize>() {
+            let layer = self.position_parameters.len() - 1;
+            return self.position_parameters[layer].len() <= n;
+        }
+        if getter::special_param(self, name).is_some() || name == "@" || name == "*" {
+            return false;
+        }
+        ! self.has_value(name) && env::var(name).is_err()
+    }
+
+    pub fn has_array_elem(&mut self, name: &str, key: &str) -> bool {
+        match getter::clone(self, name) {
+            Some(d) => d.has_key(key),
+            None    => false,
+        }
+    }
+
     pub fn len(&mut self, key: &str) -> usize {
         match getter::clone(self, key).as_mut() {
             Some(d) => d.len(),
@@ -184,6 +203,13 @@ impl DataBase {
         SingleData::set_value(&mut self.params[layer], name, val)
     }
 
+    pub fn set_unassigned_param(&mut self, name: &str, layer: Option<usize>) -> Result<(), ExecError> {
+        Self::name_check(name)?;
+        self.write_check(name)?;
+        let layer = self.get_target_layer(name, layer);
+        SingleData::set_unassigned_entry(&mut self.params[layer], name)
+    }
+
     pub fn set_array_elem(&mut self, name: &str, val: &String, pos: usize, layer: Option<usize>) -> Result<(), ExecError> {
         Self::name_check(name)?;
         self.write_check(name)?;
@@ -212,6 +238,19 @@ impl DataBase {
         AssocData::set_new_entry(&mut self.params[layer], name)
     }
 
+    /* puts values on the head of an array used as a stack such as FUNCNAME */
+    pub fn push_front(&mut self, name: &str, vals: Vec<String>) {
+        let mut array = self.get_array_all(name);
+        array.splice(0..0, vals);
+        let _ = self.set_array(name, array, None);
+    }
+
+    pub fn pop_front(&mut self, name: &str, num: usize) {
+        let mut array = self.get_array_all(name);
+        array.drain(0..std::cmp::min(num, array.len()));
+        let _ = self.set_array(name, array, None);
+    }
+
     pub fn push_local(&mut self) {
         self.params.push(HashMap::new());
         match self.param_options.last() {
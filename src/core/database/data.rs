@@ -56,9 +56,11 @@ pub trait Data {
     }
 
     fn get_all_as_array(&mut self) -> Result<Vec<String>, ExecError> {Err(ExecError::Other("not an array".to_string()))}
+    fn has_key(&self, key: &str) -> bool { key == "0" && self.is_single() }
 
     fn is_special(&self) -> bool {false}
     fn is_single(&self) -> bool {false}
+    fn is_assigned(&self) -> bool {true}
     fn is_assoc(&self) -> bool {false}
     fn is_array(&self) -> bool {false}
     fn len(&mut self) -> usize;
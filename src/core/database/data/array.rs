@@ -57,6 +57,10 @@ impl Data for ArrayData {
         Ok(self.values().clone())
     }
 
+    fn has_key(&self, key: &str) -> bool {
+        key.parse::<usize>().is_ok_and(|n| self.body.contains_key(&n))
+    }
+
     fn get_as_single(&mut self) -> Result<String, ExecError> {
         self.body.get(&0).map(|v| Ok(v.clone())).ok_or(ExecError::Other("No entry".to_string()))?
     }
@@ -74,8 +78,8 @@ impl ArrayData {
     pub fn set_elem(db_layer: &mut HashMap<String, Box<dyn Data>>,
                         name: &str, pos: usize, val: &String) -> Result<(), ExecError> {
         match db_layer.get_mut(name) {
-            Some(d) => d.set_as_array(&pos.to_string(), val),
-            None    => {
+            Some(d) if d.is_assigned() => d.set_as_array(&pos.to_string(), val),
+            _ => {
                 ArrayData::set_new_entry(db_layer, name, vec![])?;
                 Self::set_elem(db_layer, name, pos, val)
             },
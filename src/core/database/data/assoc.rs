@@ -55,6 +55,7 @@ impl Data for AssocData {
 
     fn get_as_single(&mut self) -> Result<String, ExecError> { self.last.clone().ok_or(ExecError::Other("No last input".to_string())) }
 
+    fn has_key(&self, key: &str) -> bool { self.body.contains_key(key) }
     fn is_assoc(&self) -> bool {true}
     fn len(&mut self) -> usize { self.body.len() }
 }
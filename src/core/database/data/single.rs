@@ -9,11 +9,12 @@ use super::Data;
 #[derive(Debug, Clone)]
 pub struct SingleData {
     body: String,
+    assigned: bool,
 }
 
 impl From<&str> for SingleData {
     fn from(s: &str) -> Self {
-        Self { body: s.to_string() }
+        Self { body: s.to_string(), assigned: true }
     }
 }
 
@@ -23,12 +24,14 @@ impl Data for SingleData {
 
     fn set_as_single(&mut self, value: &str) -> Result<(), ExecError> {
         self.body = value.to_string();
+        self.assigned = true;
         Ok(())
     }
 
     fn get_as_single(&mut self) -> Result<String, ExecError> { Ok(self.body.clone()) }
     fn len(&mut self) -> usize { self.body.chars().count() }
     fn is_single(&self) -> bool {true}
+    fn is_assigned(&self) -> bool {self.assigned}
 }
 
 impl SingleData {
@@ -37,6 +40,12 @@ impl SingleData {
         Ok(())
     }
 
+    /* a variable declared with local or declare without any value */
+    pub fn set_unassigned_entry(db_layer: &mut HashMap<String, Box<dyn Data>>, name: &str)-> Result<(), ExecError> {
+        db_layer.insert( name.to_string(), Box::new(SingleData { body: String::new(), assigned: false }) );
+        Ok(())
+    }
+
     pub fn set_value(db_layer: &mut HashMap<String, Box<dyn Data>>, name: &str, val: &str) -> Result<(), ExecError> {
         if env::var(name).is_ok() {
             env::set_var(name, val);
@@ -23,7 +23,9 @@ pub fn initialize(db: &mut DataBase) -> Result<(), String> {
 
     SpecialData::get(db, "SECONDS");
 
-    db.set_array("FUNCNAME", vec![], None)?;
+    for stack in ["FUNCNAME", "BASH_SOURCE", "BASH_LINENO", "BASH_ARGV", "BASH_ARGC"] {
+        db.set_array(stack, vec![], None)?;
+    }
     Ok(())
 }
 
@@ -1,77 +1,309 @@
 //SPDXFileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDXLicense-Identifier: BSD-3-Clause
 
+pub mod expansion;
+
 use crate::ShellCore;
+use crate::utils::{clock, glob};
 use rev_lines::RevLines;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
 use std::fs::OpenOptions;
 
 impl ShellCore {
     pub fn fetch_history(&mut self, pos: usize, prev: usize, prev_str: String) -> String {
         if prev < self.history.len() {
             self.history[prev] = prev_str;
-        }else{
-            self.rewritten_history.insert(prev + 1 - self.history.len(), prev_str);
         }
 
-        if pos < self.history.len() {
-            self.history[pos].clone()
-        }else{
-            self.fetch_history_file(pos + 1 - self.history.len())
+        match pos < self.history.len() {
+            true  => self.history[pos].clone(),
+            false => String::new(),
+        }
+    }
+
+    fn history_filename(&mut self, filename: Option<&str>) -> Option<String> {
+        let filename = match filename {
+            Some(f) => f.to_string(),
+            None    => self.db.get_param("HISTFILE").unwrap_or(String::new()),
+        };
+
+        match filename.as_str() {
+            "" => None,
+            _  => Some(filename),
         }
     }
 
-    pub fn fetch_history_file(&mut self, pos: usize) -> String {
-        if let Some(s) = self.rewritten_history.get(&pos) {
-            return s.to_string();
+    pub fn insert_history(&mut self, pos: usize, entry: String) {
+        self.history.insert(pos, entry);
+        self.history_times.insert(pos, Some(clock::epoch_seconds()));
+    }
+
+    pub fn history_time(&self, index: usize) -> Option<i64> {
+        self.history_times.get(index).copied().flatten()
+    }
+
+    fn ignored_by_histcontrol(&mut self, entry: &str, raw: &str) -> bool {
+        let control = self.db.get_param("HISTCONTROL").unwrap_or_default();
+        let control: Vec<&str> = control.split(':').collect();
+        let has = |c: &str| control.contains(&c) || control.contains(&"ignoreboth");
+
+        (has("ignorespace") && raw.starts_with(' '))
+        || (has("ignoredups") && self.history.get(1).map(|h| h == entry) == Some(true))
+    }
+
+    fn ignored_by_histignore(&mut self, entry: &str) -> bool {
+        let patterns = self.db.get_param("HISTIGNORE").unwrap_or_default();
+        let extglob = self.shopts.query("extglob");
+
+        patterns.split(':').filter(|p| ! p.is_empty()).any(|p| match p {
+            "&" => self.history.get(1).map(|h| h == entry) == Some(true),
+            _   => glob::parse_and_compare(&entry.to_string(), p, extglob),
+        })
+    }
+
+    fn erase_duplicates(&mut self, entry: &str) {
+        let control = self.db.get_param("HISTCONTROL").unwrap_or_default();
+        if ! control.split(':').any(|c| c == "erasedups") {
+            return;
         }
-        if pos == 0 {
-            return String::new();
+
+        for i in (1..self.history.len()).rev() {
+            if self.history[i] == entry {
+                self.delete_history(i);
+            }
         }
+    }
 
-        let mut file_line = pos - 1;
-        if let Ok(n) = self.db.get_param("HISTFILESIZE").unwrap_or(String::new()).parse::<usize>() {
-            file_line %= n;
+    pub fn set_history(&mut self, s: &str) {
+        if self.read_stdin || self.history.is_empty() {
+            return;
+        }
+        if self.history_entry_fixed {
+            self.history_entry_fixed = false;
+            return;
         }
 
-        if let Ok(hist_file) = File::open(self.db.get_param("HISTFILE").unwrap_or(String::new())){
-            let mut rev_lines = RevLines::new(BufReader::new(hist_file));
-            if let Some(Ok(s)) = rev_lines.nth(file_line) {
-                return s;
+        let raw = self.history[0].clone();
+        let entry = s.trim_end().replace("\n", "↵ \0").to_string();
+        if entry.is_empty()
+        || self.ignored_by_histcontrol(&entry, &raw)
+        || self.ignored_by_histignore(&entry) {
+            self.delete_history(0);
+            return;
+        }
+
+        self.erase_duplicates(&entry);
+        self.history[0] = entry;
+        self.history_times[0] = Some(clock::epoch_seconds());
+        self.limit_history_size();
+    }
+
+    pub fn limit_history_size(&mut self) {
+        let size = match self.db.get_param("HISTSIZE").unwrap_or_default().parse::<isize>() {
+            Ok(n) if n >= 0 => n as usize,
+            _ => return,
+        };
+
+        while self.history.len() > size {
+            self.delete_history(self.history.len() - 1);
+        }
+    }
+
+    /* the number of entries read from a history file at most */
+    fn history_read_limit(&mut self) -> usize {
+        ["HISTSIZE", "HISTFILESIZE"].iter()
+            .filter_map(|v| self.db.get_param(v).ok()?.parse::<usize>().ok())
+            .min().unwrap_or(usize::MAX)
+    }
+
+    /* the last limit entries of a history file and their timestamps, read from its end */
+    fn history_file_tail(&mut self, filename: &str, limit: usize) -> Vec<String> {
+        let file = match File::open(filename) {
+            Ok(f)  => f,
+            Err(_) => return vec![],
+        };
+
+        let mut lines = vec![];
+        let mut entries = 0;
+        for line in RevLines::new(BufReader::new(file)).map_while(Result::ok) {
+            if timestamp(&line).is_none() {
+                if entries == limit {
+                    break;
+                }
+                entries += 1;
             }
+            lines.push(line);
         }
+        lines.reverse();
+        lines
+    }
 
-        String::new()
+    /* the lines written after the first skip bytes of a history file */
+    fn history_file_after(&mut self, filename: &str, skip: u64) -> Vec<String> {
+        let mut file = match File::open(filename) {
+            Ok(f)  => f,
+            Err(_) => return vec![],
+        };
+        if file.seek(SeekFrom::Start(skip)).is_err() {
+            return vec![];
+        }
+        BufReader::new(file).lines().map_while(Result::ok).collect()
     }
 
-    pub fn write_history_to_file(&mut self) {
-        if ! self.db.flags.contains('i') || self.is_subshell {
-            return;
+    fn history_file_len(&mut self, filename: &str) -> u64 {
+        std::fs::metadata(filename).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn insert_old_history(&mut self, lines: Vec<String>) {
+        let entries = parse_history_lines(lines);
+        let pos = self.history.len() - self.history_in_file;
+        self.history_in_file += entries.len();
+        for (entry, time) in entries {
+            self.history.insert(pos, entry);
+            self.history_times.insert(pos, time);
         }
-        let filename = self.db.get_param("HISTFILE").unwrap_or(String::new());
-        if filename == "" {
-            eprintln!("sush: HISTFILE is not set");
+        self.limit_history_size();
+    }
+
+    pub fn load_history_file(&mut self) {
+        if ! self.db.flags.contains('i') {
             return;
         }
-    
-        let file = match OpenOptions::new().create(true)
-                .write(true).append(true).open(&filename) {
+        self.read_history_file(None, false);
+    }
+
+    pub fn read_history_file(&mut self, filename_arg: Option<&str>, only_new: bool) {
+        let filename = self.history_filename(filename_arg).unwrap_or_default();
+        let len = self.history_file_len(&filename);
+        let lines = match only_new {
+            true  => {
+                let skip = std::cmp::min(self.history_file_read, len);
+                self.history_file_after(&filename, skip)
+            },
+            false => {
+                let limit = self.history_read_limit();
+                self.history_file_tail(&filename, limit)
+            },
+        };
+
+        if filename_arg.is_none() {
+            self.history_file_read = len;
+        }
+        self.insert_old_history(lines);
+    }
+
+    fn write_history(&mut self, filename_arg: Option<&str>, entries: usize, append: bool) -> bool {
+        let filename = match self.history_filename(filename_arg) {
+            Some(f) => f,
+            None => {
+                eprintln!("sush: HISTFILE is not set");
+                return false;
+            },
+        };
+
+        let file = match OpenOptions::new().create(true).write(true)
+                .append(append).truncate(! append).open(&filename) {
             Ok(f) => f,
             _     => {
                 eprintln!("sush: invalid history file");
-                return;
+                return false;
             },
         };
-    
+
+        let timestamp = self.db.has_value("HISTTIMEFORMAT");
+        let mut written = 0; //bytes
         let mut f = BufWriter::new(file);
-        for h in self.history.iter().rev() {
-            if h == "" {
+        for i in (0..entries).rev() {
+            if self.history[i].is_empty() {
                 continue;
             }
-            let _ = f.write(h.as_bytes());
+            if let (true, Some(t)) = (timestamp, self.history_time(i)) {
+                let line = format!("#{}\n", t);
+                let _ = f.write(line.as_bytes());
+                written += line.len() as u64;
+            }
+            let _ = f.write(self.history[i].as_bytes());
             let _ = f.write(&[0x0A]);
+            written += self.history[i].len() as u64 + 1;
         }
         let _ = f.flush();
+        drop(f);
+
+        if filename_arg.is_none() {
+            self.history_file_read = match append {
+                true  => self.history_file_read + written,
+                false => written,
+            };
+            self.truncate_history_file(&filename);
+        }
+        self.history_in_file = self.history.len();
+        true
+    }
+
+    fn truncate_history_file(&mut self, filename: &str) {
+        let size = match self.db.get_param("HISTFILESIZE").unwrap_or_default().parse::<isize>() {
+            Ok(n) if n >= 0 => n as usize,
+            _ => return,
+        };
+
+        let kept = self.history_file_tail(filename, size);
+        let body = kept.iter().map(|l| l.to_string() + "\n").collect::<String>();
+        if body.len() as u64 >= self.history_file_len(filename) {
+            return;
+        }
+
+        if std::fs::write(filename, &body).is_ok() {
+            self.history_file_read = body.len() as u64;
+        }
+    }
+
+    pub fn append_history_to_file(&mut self, filename: Option<&str>) -> bool {
+        let new_entries = self.history.len() - self.history_in_file;
+        self.write_history(filename, new_entries, true)
+    }
+
+    pub fn overwrite_history_file(&mut self, filename: Option<&str>) -> bool {
+        self.write_history(filename, self.history.len(), false)
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_times.clear();
+        self.history_in_file = 0;
+    }
+
+    pub fn delete_history(&mut self, index: usize) {
+        if index >= self.history.len() - self.history_in_file {
+            self.history_in_file -= 1;
+        }
+        self.history.remove(index);
+        self.history_times.remove(index);
+    }
+
+    pub fn write_history_to_file(&mut self) {
+        if ! self.db.flags.contains('i') || self.is_subshell {
+            return;
+        }
+        self.append_history_to_file(None);
+    }
+}
+
+fn timestamp(line: &str) -> Option<i64> {
+    match line.strip_prefix('#') {
+        Some(n) if ! n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => n.parse::<i64>().ok(),
+        _ => None,
+    }
+}
+
+fn parse_history_lines(lines: Vec<String>) -> Vec<(String, Option<i64>)> {
+    let mut ans = vec![];
+    let mut time = None;
+    for line in lines {
+        match timestamp(&line) {
+            Some(t) => time = Some(t),
+            None    => ans.push((line, time.take())),
+        }
     }
+    ans
 }
@@ -0,0 +1,453 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::error::exec::ExecError;
+
+pub enum HistoryExpansion {
+    Unchanged,
+    Expanded(String),
+    PrintOnly(String),
+}
+
+struct Event {
+    text: String,
+    search: String,
+}
+
+fn histchars(core: &mut ShellCore) -> (char, Option<char>, Option<char>) {
+    let s: Vec<char> = match core.db.has_value("histchars") {
+        true  => core.db.get_param("histchars").unwrap_or_default().chars().collect(),
+        false => "!^#".chars().collect(),
+    };
+
+    match s.len() {
+        0 => ('\0', None, None),
+        1 => (s[0], None, None),
+        2 => (s[0], Some(s[1]), None),
+        _ => (s[0], Some(s[1]), Some(s[2])),
+    }
+}
+
+fn is_word_delimiter(c: char) -> bool {
+    " \t\n;&()|<>".contains(c)
+}
+
+pub fn split_words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut ans = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        if " \t\n".contains(chars[i]) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        if "()".contains(chars[i]) {
+            i += 1;
+        }else if "<>;&|".contains(chars[i]) {
+            while i < chars.len() && "<>;&|".contains(chars[i]) {
+                i += 1;
+            }
+        }else{
+            let mut quote = None;
+            while i < chars.len() {
+                let c = chars[i];
+                if c == '\\' && quote != Some('\'') {
+                    i += 2;
+                    continue;
+                }
+                match quote {
+                    Some(q) if c == q => quote = None,
+                    Some(_) => {},
+                    None if "'\"`".contains(c) => quote = Some(c),
+                    None if is_word_delimiter(c) => break,
+                    None => {},
+                }
+                i += 1;
+            }
+        }
+        ans.push(chars[start..std::cmp::min(i, chars.len())].iter().collect());
+    }
+    ans
+}
+
+fn inhibited(chars: &[char], pos: usize, extglob: bool) -> bool {
+    match chars.get(pos+1) {
+        None | Some(' ') | Some('\t') | Some('\n') | Some('\r') | Some('=') => return true,
+        Some('(') => return extglob,
+        _ => {},
+    }
+
+    (pos > 0 && chars[pos-1] == '$')
+    || (pos > 1 && chars[pos-1] == '{' && chars[pos-2] == '$')
+}
+
+impl ShellCore {
+    fn history_list(&mut self) -> Vec<String> {
+        let skip = match self.read_stdin {
+            true  => 0,
+            false => 1,
+        };
+        self.history.iter().skip(skip).map(|h| h.replace("↵ \0", "\n")).collect()
+    }
+
+    pub fn expand_history(&mut self, line: &str) -> Result<HistoryExpansion, ExecError> {
+        let (hchar, qchar, cchar) = histchars(self);
+        if hchar == '\0' {
+            return Ok(HistoryExpansion::Unchanged);
+        }
+
+        let mut source = line.to_string();
+        if qchar.is_some() && line.starts_with(qchar.unwrap()) {
+            source = format!("{0}{0}:s{1}", hchar, line);
+        }else if ! line.contains(hchar) {
+            return Ok(HistoryExpansion::Unchanged);
+        }
+
+        let extglob = self.shopts.query("extglob");
+        let chars: Vec<char> = source.chars().collect();
+        let mut list = None;
+        let mut ans = String::new();
+        let mut print_only = false;
+        let mut changed = false;
+        let mut in_squote = false;
+        let mut in_dquote = false;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            if c == '\\' && ! in_squote {
+                ans.push(c);
+                if let Some(next) = chars.get(i+1) {
+                    ans.push(*next);
+                }
+                i += 2;
+                continue;
+            }
+
+            if c == '\'' && ! in_dquote {
+                in_squote = ! in_squote;
+            }else if c == '"' && ! in_squote {
+                in_dquote = ! in_dquote;
+            }else if Some(c) == cchar && ! in_squote && ! in_dquote
+            && (i == 0 || " \t\n".contains(chars[i-1])) {
+                ans += &chars[i..].iter().collect::<String>();
+                break;
+            }
+
+            if c != hchar || in_squote || inhibited(&chars, i, extglob)
+            || (in_dquote && chars.get(i+1) == Some(&'"')) {
+                ans.push(c);
+                i += 1;
+                continue;
+            }
+
+            if list.is_none() {
+                list = Some(self.history_list());
+            }
+            let (text, len, print) = self.expand_one(&chars[i..], &ans, list.as_ref().unwrap(), in_dquote)?;
+            ans += &text;
+            print_only |= print;
+            changed = true;
+            i += len;
+        }
+
+        match (changed, print_only) {
+            (false, _)    => Ok(HistoryExpansion::Unchanged),
+            (true, true)  => Ok(HistoryExpansion::PrintOnly(ans)),
+            (true, false) => Ok(HistoryExpansion::Expanded(ans)),
+        }
+    }
+
+    fn expand_one(&mut self, chars: &[char], current: &str, list: &[String],
+                  in_dquote: bool) -> Result<(String, usize, bool), ExecError> {
+        let (event, mut pos) = self.parse_event(chars, current, list, in_dquote)?;
+
+        let words = split_words(&event.text);
+        let (mut text, len) = select_words(&chars[pos..], &words, &event.search).map_err(|len| {
+            let spec: String = chars[..pos+len].iter().collect();
+            ExecError::HistoryBadWordSpecifier(spec)
+        })?;
+        pos += len;
+
+        let mut print_only = false;
+        while chars.get(pos) == Some(&':') {
+            let len = self.apply_modifier(&chars[pos+1..], &mut text, &mut print_only)?;
+            pos += len + 1;
+        }
+
+        Ok((text, pos, print_only))
+    }
+
+    fn parse_event(&mut self, chars: &[char], current: &str, list: &[String],
+                   in_dquote: bool) -> Result<(Event, usize), ExecError> {
+        let hchar = chars[0];
+        let not_found = |len: usize| {
+            let s: String = chars[..len].iter().collect();
+            ExecError::HistoryEventNotFound(s)
+        };
+        let nth = |n: usize| -> Option<Event> {
+            list.get(n).map(|t| Event{ text: t.clone(), search: String::new() })
+        };
+
+        let next = chars[1];
+        if next == hchar {
+            return nth(0).map(|e| (e, 2)).ok_or(not_found(2));
+        }
+        if "^$*%:".contains(next) {
+            return nth(0).map(|e| (e, 1)).ok_or(not_found(1));
+        }
+        if next == '#' {
+            return Ok((Event{ text: current.to_string(), search: String::new() }, 2));
+        }
+
+        if next.is_ascii_digit() || next == '-' {
+            let len = 1 + chars[2..].iter().take_while(|c| c.is_ascii_digit()).count() + 1;
+            let num_str: String = chars[1..len].iter().collect();
+            let num = match num_str.parse::<isize>() {
+                Ok(n) if n != 0 => n,
+                _ => return Err(not_found(len)),
+            };
+
+            let index = match num < 0 {
+                true  => (- num - 1) as usize,
+                false => {
+                    let total = list.len() + 1;
+                    match (num as usize) < total {
+                        true  => total - 1 - num as usize,
+                        false => return Err(not_found(len)),
+                    }
+                },
+            };
+            return nth(index).map(|e| (e, len)).ok_or(not_found(len));
+        }
+
+        if next == '?' {
+            let mut len = 2;
+            while len < chars.len() && chars[len] != '?' && chars[len] != '\n' {
+                len += 1;
+            }
+            let key: String = chars[2..len].iter().collect();
+            if len < chars.len() && chars[len] == '?' {
+                len += 1;
+            }
+            let key = match key.is_empty() {
+                true  => self.history_last_search.clone(),
+                false => key,
+            };
+            self.history_last_search = key.clone();
+
+            return match list.iter().find(|h| ! key.is_empty() && h.contains(&key)) {
+                Some(h) => Ok((Event{ text: h.clone(), search: key }, len)),
+                None    => Err(not_found(len)),
+            };
+        }
+
+        let mut len = 1;
+        while len < chars.len() && ! is_word_delimiter(chars[len])
+        && chars[len] != ':' && ! (in_dquote && chars[len] == '"') {
+            len += 1;
+        }
+        let key: String = chars[1..len].iter().collect();
+        match list.iter().find(|h| h.starts_with(&key)) {
+            Some(h) => Ok((Event{ text: h.clone(), search: String::new() }, len)),
+            None    => Err(not_found(len)),
+        }
+    }
+
+    fn apply_modifier(&mut self, chars: &[char], text: &mut String,
+                      print_only: &mut bool) -> Result<usize, ExecError> {
+        let unrecognized = |len: usize| {
+            let s: String = chars[..len].iter().collect();
+            ExecError::HistoryUnrecognizedModifier(":".to_owned() + &s)
+        };
+
+        match chars.first() {
+            Some('h') => {
+                if let Some(p) = text.rfind('/') {
+                    text.truncate(std::cmp::max(p, 1));
+                }
+            },
+            Some('t') => {
+                if let Some(p) = text.rfind('/') {
+                    *text = text[p+1..].to_string();
+                }
+            },
+            Some('r') => {
+                if let Some(p) = text.rfind('.') {
+                    if ! text[p..].contains('/') {
+                        text.truncate(p);
+                    }
+                }
+            },
+            Some('e') => {
+                if let Some(p) = text.rfind('.') {
+                    if ! text[p..].contains('/') {
+                        *text = text[p..].to_string();
+                    }
+                }
+            },
+            Some('p') => *print_only = true,
+            Some('q') => *text = quote(text),
+            Some('x') => {
+                *text = split_words(text).iter().map(|w| quote(w))
+                        .collect::<Vec<String>>().join(" ");
+            },
+            Some('s') | Some('&') => return self.substitute(chars, text, false, false),
+            Some('g') | Some('a') | Some('G') => {
+                match chars.get(1) {
+                    Some('s') | Some('&') => {
+                        let len = self.substitute(&chars[1..], text, true, chars[0] == 'G')?;
+                        return Ok(len + 1);
+                    },
+                    _ => return Err(unrecognized(2)),
+                }
+            },
+            _ => return Err(unrecognized(1)),
+        }
+        Ok(1)
+    }
+
+    fn substitute(&mut self, chars: &[char], text: &mut String,
+                  global: bool, each_word: bool) -> Result<usize, ExecError> {
+        let mut len = 1;
+        if chars[0] == 's' {
+            let delim = match chars.get(1) {
+                Some(c) => *c,
+                None => return Err(ExecError::HistorySubstitutionFailed(":s".to_string())),
+            };
+            len = 2;
+            let (mut old, l) = read_delimited(&chars[len..], delim, None);
+            len += l;
+            if old.is_empty() {
+                old = match &self.history_last_subst {
+                    Some((o, _)) => o.clone(),
+                    None => self.history_last_search.clone(),
+                };
+            }
+            let (new, l) = read_delimited(&chars[len..], delim, Some(&old));
+            len += l;
+            self.history_last_subst = Some((old, new));
+        }
+
+        let spec: String = chars[..len].iter().collect();
+        let failed = || ExecError::HistorySubstitutionFailed(":".to_owned() + &spec);
+        let (old, new) = match &self.history_last_subst {
+            Some((o, n)) if ! o.is_empty() => (o.clone(), n.clone()),
+            _ => return Err(failed()),
+        };
+        if ! text.contains(&old) {
+            return Err(failed());
+        }
+
+        *text = match (global, each_word) {
+            (false, _)    => text.replacen(&old, &new, 1),
+            (true, false) => text.replace(&old, &new),
+            (true, true)  => split_words(text).iter().map(|w| w.replacen(&old, &new, 1))
+                             .collect::<Vec<String>>().join(" "),
+        };
+        Ok(len)
+    }
+}
+
+fn read_delimited(chars: &[char], delim: char, old: Option<&str>) -> (String, usize) {
+    let mut ans = String::new();
+    let mut i = 0;
+    while i < chars.len() && chars[i] != delim && chars[i] != '\n' {
+        if chars[i] == '\\' && (chars.get(i+1) == Some(&delim) || chars.get(i+1) == Some(&'&')) {
+            ans.push(chars[i+1]);
+            i += 2;
+            continue;
+        }
+        match (chars[i], old) {
+            ('&', Some(o)) => ans += o,
+            (c, _) => ans.push(c),
+        }
+        i += 1;
+    }
+
+    if i < chars.len() && chars[i] == delim {
+        i += 1;
+    }
+    (ans, i)
+}
+
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace("'", "'\\''"))
+}
+
+fn parse_word_index(chars: &[char], last: usize) -> Option<(usize, usize)> {
+    match chars.first() {
+        Some('^') => Some((1, 1)),
+        Some('$') => Some((last, 1)),
+        Some(c) if c.is_ascii_digit() => {
+            let len = chars.iter().take_while(|c| c.is_ascii_digit()).count();
+            let s: String = chars[..len].iter().collect();
+            s.parse::<usize>().ok().map(|n| (n, len))
+        },
+        _ => None,
+    }
+}
+
+/* returns the selected words and the length of the designator;
+ * the length is also returned on failure for the error message */
+fn select_words(chars: &[char], words: &[String], search: &str) -> Result<(String, usize), usize> {
+    let (skip, spec) = match chars.first() {
+        Some(':') => match chars.get(1) {
+            Some(c) if c.is_ascii_digit() || "^$*-%".contains(*c) => (1, &chars[1..]),
+            _ => return Ok((words.join(" "), 0)),
+        },
+        Some(c) if "^$*-%".contains(*c) => (0, chars),
+        _ => return Ok((words.join(" "), 0)),
+    };
+
+    let last = words.len().saturating_sub(1);
+    let range = |from: usize, to: usize, len: usize| -> Result<(String, usize), usize> {
+        match ! words.is_empty() && from <= to && to <= last {
+            true  => Ok((words[from..=to].join(" "), len)),
+            false => Err(len),
+        }
+    };
+
+    if spec[0] == '*' {
+        return match words.len() {
+            0 => Err(skip + 1),
+            1 => Ok((String::new(), skip + 1)),
+            _ => Ok((words[1..].join(" "), skip + 1)),
+        };
+    }
+    if spec[0] == '%' {
+        return match words.iter().find(|w| ! search.is_empty() && w.contains(search)) {
+            Some(w) => Ok((w.clone(), skip + 1)),
+            None    => Err(skip + 1),
+        };
+    }
+
+    let digits = spec.iter().take_while(|c| c.is_ascii_digit()).count().max(1);
+    let (from, mut len) = match spec[0] {
+        '-' => (0, 0),
+        _   => parse_word_index(spec, last).ok_or(skip + digits)?,
+    };
+
+    match spec.get(len) {
+        Some('*') => match words.is_empty() || from > last {
+            true  => Err(skip + len + 1),
+            false => Ok((words[from..].join(" "), skip + len + 1)),
+        },
+        Some('-') => {
+            len += 1;
+            match parse_word_index(&spec[len..], last) {
+                Some((to, l)) => range(from, to, skip + len + l),
+                None => match last {
+                    0 => Err(skip + len),
+                    _ => range(from, last - 1, skip + len),
+                },
+            }
+        },
+        _ => range(from, from, skip + len),
+    }
+}
@@ -0,0 +1,520 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use std::collections::BTreeMap;
+use std::fs;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Binding {
+    Function(String),
+    Macro(String),
+    Command(String),
+}
+
+pub enum Lookup {
+    Found(Binding),
+    Prefix,
+    NotFound,
+}
+
+pub const FUNCTIONS: &[&str] = &[
+    "abort", "accept-line", "backward-char", "backward-delete-char",
+    "backward-kill-line", "backward-kill-word", "backward-word",
+    "beginning-of-history", "beginning-of-line", "capitalize-word",
+    "clear-screen", "complete", "delete-char", "downcase-word",
+    "edit-and-execute-command", "emacs-editing-mode",
+    "end-of-history", "end-of-line", "forward-char", "forward-search-history",
+    "forward-word", "history-search-backward", "history-search-forward",
+    "kill-line", "kill-whole-line", "kill-word", "menu-complete",
+    "menu-complete-backward", "next-history",
+    "possible-completions", "previous-history", "quoted-insert",
+    "redraw-current-line", "reverse-search-history", "self-insert",
+    "tab-insert", "transpose-chars", "undo", "unix-line-discard", "unix-word-rubout",
+    "upcase-word", "vi-append-eol", "vi-append-mode", "vi-arg-digit",
+    "vi-change-case", "vi-change-char", "vi-change-to", "vi-char-search",
+    "vi-delete", "vi-delete-to", "vi-editing-mode", "vi-end-word",
+    "vi-first-print", "vi-insert-beg", "vi-insertion-mode", "vi-movement-mode",
+    "vi-next-word", "vi-prev-word", "vi-put", "vi-redo", "vi-rubout",
+    "vi-search", "vi-search-again", "vi-subst", "vi-undo", "vi-yank-to",
+    "yank",
+];
+
+const EMACS_BINDINGS: &[(&str, &str)] = &[
+    ("\\C-a", "beginning-of-line"), ("\\C-b", "backward-char"),
+    ("\\C-d", "delete-char"), ("\\C-e", "end-of-line"),
+    ("\\C-f", "forward-char"), ("\\C-g", "abort"),
+    ("\\C-h", "backward-delete-char"), ("\\C-i", "complete"),
+    ("\\C-j", "accept-line"), ("\\C-k", "kill-line"),
+    ("\\C-l", "clear-screen"), ("\\C-m", "accept-line"),
+    ("\\C-n", "next-history"), ("\\C-p", "previous-history"),
+    ("\\C-r", "reverse-search-history"), ("\\C-s", "forward-search-history"),
+    ("\\C-t", "transpose-chars"), ("\\C-u", "unix-line-discard"),
+    ("\\C-v", "quoted-insert"), ("\\C-w", "unix-word-rubout"),
+    ("\\C-y", "yank"), ("\\C-?", "backward-delete-char"),
+    ("\\e\\C-?", "backward-kill-word"), ("\\e\\C-h", "backward-kill-word"),
+    ("\\e\\C-i", "tab-insert"), ("\\e<", "beginning-of-history"),
+    ("\\e>", "end-of-history"), ("\\e=", "possible-completions"),
+    ("\\e?", "possible-completions"), ("\\eb", "backward-word"),
+    ("\\ec", "capitalize-word"), ("\\ed", "kill-word"),
+    ("\\ef", "forward-word"), ("\\el", "downcase-word"),
+    ("\\eu", "upcase-word"), ("\\e[A", "previous-history"),
+    ("\\e[B", "next-history"), ("\\e[C", "forward-char"),
+    ("\\e[D", "backward-char"), ("\\e[H", "beginning-of-line"),
+    ("\\e[F", "end-of-line"), ("\\e[3~", "delete-char"),
+    ("\\C-_", "undo"), ("\\C-x\\C-u", "undo"),
+    ("\\C-x\\C-e", "edit-and-execute-command"), ("\\e\\C-j", "vi-editing-mode"),
+    ("\\e[Z", "menu-complete-backward"),
+];
+
+const VI_INSERT_BINDINGS: &[(&str, &str)] = &[
+    ("\\e", "vi-movement-mode"), ("\\C-d", "delete-char"),
+    ("\\C-h", "backward-delete-char"), ("\\C-?", "backward-delete-char"),
+    ("\\C-i", "complete"), ("\\C-j", "accept-line"),
+    ("\\C-m", "accept-line"), ("\\C-r", "reverse-search-history"),
+    ("\\C-s", "forward-search-history"), ("\\C-t", "transpose-chars"),
+    ("\\C-u", "unix-line-discard"), ("\\C-v", "quoted-insert"),
+    ("\\C-w", "unix-word-rubout"), ("\\C-y", "yank"),
+    ("\\e[A", "previous-history"), ("\\e[B", "next-history"),
+    ("\\e[C", "forward-char"), ("\\e[D", "backward-char"),
+    ("\\e[H", "beginning-of-line"), ("\\e[F", "end-of-line"),
+    ("\\e[3~", "delete-char"), ("\\e[Z", "menu-complete-backward"),
+];
+
+const VI_COMMAND_BINDINGS: &[(&str, &str)] = &[
+    (" ", "forward-char"), ("$", "end-of-line"), ("+", "next-history"),
+    (",", "vi-char-search"), ("-", "previous-history"), (".", "vi-redo"),
+    ("/", "vi-search"), ("0", "beginning-of-line"), ("1", "vi-arg-digit"),
+    ("2", "vi-arg-digit"), ("3", "vi-arg-digit"), ("4", "vi-arg-digit"),
+    ("5", "vi-arg-digit"), ("6", "vi-arg-digit"), ("7", "vi-arg-digit"),
+    ("8", "vi-arg-digit"), ("9", "vi-arg-digit"), (";", "vi-char-search"),
+    ("?", "vi-search"), ("A", "vi-append-eol"), ("B", "vi-prev-word"),
+    ("C", "vi-change-to"), ("D", "vi-delete-to"), ("E", "vi-end-word"),
+    ("F", "vi-char-search"), ("I", "vi-insert-beg"), ("N", "vi-search-again"),
+    ("P", "vi-put"), ("S", "vi-subst"), ("T", "vi-char-search"),
+    ("W", "vi-next-word"), ("X", "vi-rubout"), ("Y", "vi-yank-to"),
+    ("^", "vi-first-print"), ("a", "vi-append-mode"), ("b", "vi-prev-word"),
+    ("c", "vi-change-to"), ("d", "vi-delete-to"), ("e", "vi-end-word"),
+    ("f", "vi-char-search"), ("h", "backward-char"), ("i", "vi-insertion-mode"),
+    ("j", "next-history"), ("k", "previous-history"), ("l", "forward-char"),
+    ("n", "vi-search-again"), ("p", "vi-put"), ("r", "vi-change-char"),
+    ("s", "vi-subst"), ("t", "vi-char-search"), ("u", "vi-undo"),
+    ("v", "edit-and-execute-command"), ("w", "vi-next-word"), ("x", "vi-delete"),
+    ("y", "vi-yank-to"), ("~", "vi-change-case"),
+    ("\\C-d", "delete-char"), ("\\C-h", "backward-char"),
+    ("\\C-?", "backward-char"), ("\\C-j", "accept-line"),
+    ("\\C-l", "clear-screen"), ("\\C-m", "accept-line"),
+    ("\\C-r", "reverse-search-history"), ("\\C-s", "forward-search-history"),
+    ("\\e[A", "previous-history"), ("\\e[B", "next-history"),
+    ("\\e[C", "forward-char"), ("\\e[D", "backward-char"),
+    ("\\e[H", "beginning-of-line"), ("\\e[F", "end-of-line"),
+    ("\\e[3~", "vi-delete"),
+];
+
+const VARIABLES: &[(&str, &str)] = &[
+    ("bell-style", "audible"),
+    ("completion-ignore-case", "off"),
+    ("editing-mode", "emacs"),
+    ("keymap", "emacs"),
+    ("show-all-if-ambiguous", "off"),
+];
+
+pub struct KeyMap {
+    maps: BTreeMap<String, BTreeMap<String, Binding>>,
+    variables: BTreeMap<String, String>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/* the canonical name of a keymap accepted by bind -m and "set keymap" */
+fn keymap_name(name: &str) -> Option<&'static str> {
+    match name {
+        "emacs" | "emacs-standard" | "emacs-meta" | "emacs-ctlx" => Some("emacs"),
+        "vi" | "vi-command" | "vi-move" => Some("vi-command"),
+        "vi-insert" => Some("vi-insert"),
+        _ => None,
+    }
+}
+
+impl KeyMap {
+    pub fn new() -> Self {
+        let mut keymap = KeyMap {
+            maps: BTreeMap::new(),
+            variables: BTreeMap::new(),
+        };
+
+        let defaults = [("emacs", EMACS_BINDINGS), ("vi-insert", VI_INSERT_BINDINGS),
+                        ("vi-command", VI_COMMAND_BINDINGS)];
+        for (name, bindings) in defaults {
+            let map = bindings.iter()
+                      .map(|(seq, f)| (parse_keyseq(seq).unwrap(), Binding::Function(f.to_string())))
+                      .collect();
+            keymap.maps.insert(name.to_string(), map);
+        }
+        for (name, value) in VARIABLES {
+            keymap.variables.insert(name.to_string(), value.to_string());
+        }
+        keymap
+    }
+
+    pub fn lookup(&self, map: &str, seq: &str) -> Lookup {
+        let bindings = &self.maps[map];
+        if let Some(b) = bindings.get(seq) {
+            return Lookup::Found(b.clone());
+        }
+
+        match bindings.range(seq.to_string()..).next() {
+            Some((k, _)) if k.starts_with(seq) => Lookup::Prefix,
+            _ => Lookup::NotFound,
+        }
+    }
+
+    /* the keymap that bind and inputrc lines modify */
+    fn target(&mut self) -> &mut BTreeMap<String, Binding> {
+        let name = self.variable("keymap");
+        self.maps.get_mut(&name).unwrap()
+    }
+
+    pub fn select(&mut self, name: &str) -> bool {
+        match keymap_name(name) {
+            Some(n) => {
+                self.variables.insert("keymap".to_string(), n.to_string());
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn bind(&mut self, seq: &str, binding: Binding) {
+        self.target().insert(seq.to_string(), binding);
+    }
+
+    pub fn unbind(&mut self, seq: &str) -> bool {
+        self.target().remove(seq).is_some()
+    }
+
+    pub fn unbind_function(&mut self, func: &str) {
+        self.target().retain(|_, b| *b != Binding::Function(func.to_string()));
+    }
+
+    pub fn bindings(&mut self) -> &BTreeMap<String, Binding> {
+        self.target()
+    }
+
+    pub fn keyseqs_of(&mut self, func: &str) -> Vec<String> {
+        let target = Binding::Function(func.to_string());
+        self.target().iter()
+            .filter(|(_, b)| **b == target)
+            .map(|(k, _)| k.clone())
+            .collect()
+    }
+
+    pub fn variables(&self) -> &BTreeMap<String, String> {
+        &self.variables
+    }
+
+    pub fn variable(&self, name: &str) -> String {
+        self.variables.get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn variable_on(&self, name: &str) -> bool {
+        self.variable(name) == "on"
+    }
+
+    pub fn set_editing_mode(&mut self, mode: &str) {
+        self.variables.insert("editing-mode".to_string(), mode.to_string());
+        match mode {
+            "vi" => self.select("vi-insert"),
+            _    => self.select("emacs"),
+        };
+    }
+
+    fn set_variable(&mut self, name: &str, value: &str) {
+        match name {
+            "keymap" => { self.select(value); return; },
+            "editing-mode" if value == "vi" || value == "emacs" => {
+                self.set_editing_mode(value);
+                return;
+            },
+            "editing-mode" => return,
+            _ => {},
+        }
+
+        let value = match self.variables.get(name).map(|v| v.as_str()) {
+            None => return,
+            Some("on") | Some("off") => {
+                match value.eq_ignore_ascii_case("on") || value == "1" {
+                    true  => "on",
+                    false => "off",
+                }
+            },
+            Some(_) => value,
+        };
+        self.variables.insert(name.to_string(), value.to_string());
+    }
+
+    /* a line of inputrc except conditional constructs */
+    pub fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        if let Some(rest) = line.strip_prefix("set ") {
+            let mut words = rest.split_whitespace();
+            let name = words.next().unwrap_or("");
+            let value = words.next().unwrap_or("");
+            self.set_variable(name, value);
+            return Ok(());
+        }
+
+        let (seq, value) = split_binding(line)?;
+        let binding = match value.chars().next() {
+            Some('"') | Some('\'') => Binding::Macro(parse_keyseq(&unquote(value))?),
+            Some(_) => {
+                let func = value.split_whitespace().next().unwrap();
+                if ! FUNCTIONS.contains(&func) {
+                    return Err(format!("{}: unknown function name", func));
+                }
+                Binding::Function(func.to_string())
+            },
+            None => return Err(format!("{}: no function name", line)),
+        };
+        self.bind(&seq, binding);
+        Ok(())
+    }
+
+    pub fn bind_command(&mut self, arg: &str) -> Result<(), String> {
+        let (seq, command) = split_binding(arg)?;
+        let command = match command.starts_with('"') || command.starts_with('\'') {
+            true  => unquote(command),
+            false => command.to_string(),
+        };
+        self.bind(&seq, Binding::Command(command));
+        Ok(())
+    }
+
+    fn condition(&self, cond: &str, term: &str) -> bool {
+        if let Some(mode) = cond.strip_prefix("mode=") {
+            return self.variable("editing-mode") == mode;
+        }
+        if let Some(t) = cond.strip_prefix("term=") {
+            return term == t || term.split('-').next() == Some(t);
+        }
+
+        ["bash", "sush"].contains(&cond.to_lowercase().as_str())
+    }
+
+    pub fn read_file(&mut self, path: &str, term: &str) -> Result<Vec<String>, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        };
+
+        let mut errors = vec![];
+        let mut skipping = vec![];
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            let (directive, arg) = match line.strip_prefix('$') {
+                Some(d) => d.split_once(char::is_whitespace).unwrap_or((d, "")),
+                None    => ("", ""),
+            };
+
+            match directive {
+                "if"    => skipping.push(! self.condition(arg.trim(), term)),
+                "else"  => if let Some(s) = skipping.last_mut() { *s = ! *s },
+                "endif" => { skipping.pop(); },
+                _ if skipping.contains(&true) => {},
+                "include" => if let Ok(mut e) = self.read_file(arg.trim(), term) {
+                    errors.append(&mut e);
+                },
+                _ => if let Err(e) = self.parse_line(line) {
+                    errors.push(format!("{}: line {}: {}", path, n+1, e));
+                },
+            }
+        }
+        Ok(errors)
+    }
+}
+
+fn unquote(s: &str) -> String {
+    let quote = s.chars().next().unwrap_or('"');
+    let mut ans = String::new();
+    let mut escaped = false;
+    for c in s.chars().skip(1) {
+        if ! escaped && c == quote {
+            break;
+        }
+        escaped = ! escaped && c == '\\';
+        ans.push(c);
+    }
+    ans
+}
+
+fn split_binding(line: &str) -> Result<(String, &str), String> {
+    if line.starts_with('"') {
+        let body = unquote(line);
+        let rest = match line.get(body.len()+2..) {
+            Some(r) => r.trim_start(),
+            None => return Err(format!("{}: no closing `\"' in key binding", line)),
+        };
+        return match rest.strip_prefix(':') {
+            Some(value) => Ok((parse_keyseq(&body)?, value.trim())),
+            None => Err(format!("{}: missing colon separator", line)),
+        };
+    }
+
+    match line.split_once(':') {
+        Some((name, value)) => Ok((parse_keyname(name.trim())?, value.trim())),
+        None => Err(format!("{}: missing colon separator", line)),
+    }
+}
+
+fn control(c: char) -> char {
+    match c {
+        '?' => '\x7f',
+        _   => ((c.to_ascii_uppercase() as u8) & 0x1f) as char,
+    }
+}
+
+/* translates readline escapes like \C-a, \M-b and \e into raw characters */
+pub fn parse_keyseq(s: &str) -> Result<String, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut ans = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '\\' || i + 1 >= chars.len() {
+            ans.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let rest: String = chars[i+1..].iter().collect();
+        if rest.starts_with("C-") && i + 3 < chars.len() {
+            ans.push(control(chars[i+3]));
+            i += 4;
+            continue;
+        }
+        if rest.starts_with("M-") && i + 3 < chars.len() {
+            ans.push('\x1b');
+            i += 3;
+            continue;
+        }
+
+        let (c, len) = match chars[i+1] {
+            'a' => ('\x07', 2), 'b' => ('\x08', 2), 'd' => ('\x7f', 2),
+            'e' => ('\x1b', 2), 'f' => ('\x0c', 2), 'n' => ('\n', 2),
+            'r' => ('\r', 2),   't' => ('\t', 2),   'v' => ('\x0b', 2),
+            '0'..='7' => {
+                let digits: String = rest.chars().take(3).take_while(|c| c.is_digit(8)).collect();
+                let n = u32::from_str_radix(&digits, 8).unwrap();
+                (char::from_u32(n).unwrap_or('\0'), digits.len() + 1)
+            },
+            'x' => {
+                let digits: String = rest.chars().skip(1).take(2).take_while(|c| c.is_ascii_hexdigit()).collect();
+                match u32::from_str_radix(&digits, 16) {
+                    Ok(n) => (char::from_u32(n).unwrap_or('\0'), digits.len() + 2),
+                    _     => ('x', 2),
+                }
+            },
+            c => (c, 2),
+        };
+        ans.push(c);
+        i += len;
+    }
+    Ok(ans)
+}
+
+/* translates key names like Control-u and Meta-Rubout */
+pub fn parse_keyname(s: &str) -> Result<String, String> {
+    let mut name = s;
+    let mut ctrl = false;
+    let mut meta = false;
+
+    loop {
+        let lower = name.to_lowercase();
+        if lower.starts_with("control-") {
+            (ctrl, name) = (true, &name[8..]);
+        }else if lower.starts_with("c-") {
+            (ctrl, name) = (true, &name[2..]);
+        }else if lower.starts_with("meta-") {
+            (meta, name) = (true, &name[5..]);
+        }else if lower.starts_with("m-") {
+            (meta, name) = (true, &name[2..]);
+        }else{
+            break;
+        }
+    }
+
+    let c = match name.to_lowercase().as_str() {
+        "del" | "rubout" => '\x7f',
+        "esc" | "escape" => '\x1b',
+        "lfd" | "newline" => '\n',
+        "ret" | "return" => '\r',
+        "spc" | "space" => ' ',
+        "tab" => '\t',
+        _ => match name.chars().count() {
+            1 => name.chars().next().unwrap(),
+            _ => return Err(format!("{}: invalid key name", s)),
+        },
+    };
+
+    let mut ans = String::new();
+    if meta {
+        ans.push('\x1b');
+    }
+    ans.push(if ctrl { control(c) } else { c });
+    Ok(ans)
+}
+
+/* the inverse of parse_keyseq for bind -p */
+pub fn keyseq_to_string(seq: &str) -> String {
+    let mut ans = String::new();
+    for c in seq.chars() {
+        match c {
+            '\x1b' => ans += "\\e",
+            '\x7f' => ans += "\\C-?",
+            '"'    => ans += "\\\"",
+            '\\'   => ans += "\\\\",
+            c if (c as u32) < 0x20 => {
+                ans += "\\C-";
+                ans.push(((c as u8) + 0x60) as char);
+            },
+            c => ans.push(c),
+        }
+    }
+    ans
+}
+
+impl ShellCore {
+    pub fn load_inputrc(&mut self) {
+        if ! self.db.flags.contains('i') {
+            return;
+        }
+
+        let home = self.db.get_param("HOME").unwrap_or_default();
+        let path = match self.db.get_param("INPUTRC") {
+            Ok(p) if ! p.is_empty() => p,
+            _ => match fs::metadata(home.clone() + "/.inputrc") {
+                Ok(_)  => home + "/.inputrc",
+                Err(_) => "/etc/inputrc".to_string(),
+            },
+        };
+
+        let term = self.db.get_param("TERM").unwrap_or_default();
+        let _ = self.keymap.read_file(&path, &term);
+        self.sync_editing_mode();
+    }
+
+    /* reflects "set editing-mode" of readline on set -o */
+    pub fn sync_editing_mode(&mut self) {
+        let vi = self.keymap.variable("editing-mode") == "vi";
+        self.options.set("vi", vi);
+        self.options.set("emacs", ! vi);
+    }
+}
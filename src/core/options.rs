@@ -3,7 +3,26 @@
 
 use std::collections::HashMap;
 
-#[derive(Debug, Default)]
+/* the options of set which also appear as flags in $-;
+ * errtrace (-E) and functrace (-T) are only recorded: they do nothing
+ * until there are ERR, DEBUG and RETURN traps */
+pub const FLAG_NAMES: [(char, &str); 19] = [
+    ('a', "allexport"), ('b', "notify"), ('B', "braceexpand"), ('C', "noclobber"),
+    ('e', "errexit"), ('E', "errtrace"), ('f', "noglob"), ('h', "hashall"),
+    ('H', "histexpand"), ('k', "keyword"), ('m', "monitor"), ('n', "noexec"),
+    ('p', "privileged"), ('P', "physical"), ('t', "onecmd"), ('T', "functrace"),
+    ('u', "nounset"), ('v', "verbose"), ('x', "xtrace"),
+];
+
+pub fn flag_of(name: &str) -> Option<char> {
+    FLAG_NAMES.iter().find(|f| f.1 == name).map(|f| f.0)
+}
+
+pub fn flag_of_char(ch: char) -> Option<&'static str> {
+    FLAG_NAMES.iter().find(|f| f.0 == ch).map(|f| f.1)
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct Options {
     opts: HashMap<String, bool>,
 }
@@ -12,29 +31,43 @@ impl Options {
     pub fn new_as_basic_opts() -> Options {
         let mut options = Options::default();
         options.opts.insert("pipefail".to_string(), false);
-        options.opts.insert("noglob".to_string(), false);
+        options.opts.insert("emacs".to_string(), true);
+        options.opts.insert("vi".to_string(), false);
+        options.opts.insert("ignoreeof".to_string(), false);
+        options.opts.insert("interactive-comments".to_string(), true);
+        options.opts.insert("nolog".to_string(), false);
+        options.opts.insert("posix".to_string(), false);
+        options
+    }
+
+    /* a copy with the options given by the flags in $- */
+    pub fn with_flags(&self, flags: &str) -> Options {
+        let mut options = self.clone();
+        for (c, name) in FLAG_NAMES {
+            options.opts.insert(name.to_string(), flags.contains(c));
+        }
         options
     }
 
     pub fn new_as_shopts() -> Options {
         let mut options = Options::default();
-        let opt_strs = vec!["autocd", "cdable_vars", "cdspell", "checkhash",
+        let opt_strs = vec!["autocd", "autosuggest", "cdable_vars", "cdspell", "checkhash",
                    "checkjobs", "checkwinsize", "cmdhist", "compat31",
                    "compat32", "compat40", "compat41", "dirspell",
                    "dotglob", "execfail", "expand_aliases", "extdebug",
                    "extglob", "extquote", "failglob", "force_fignore",
                    "globstar", "gnu_errfmt", "histappend", "histreedit",
-                   "histverify", "hostcomplete", "huponexit", "interactive_comments",
+                   "histverify", "hostcomplete", "huponexit", "inherit_errexit", "interactive_comments",
                    "lastpipe", "lithist", "login_shell", "mailwarn",
                    "no_empty_cmd_completion", "nocaseglob", "nocasematch", "nullglob",
                    "promptvars", "restricted_shell", "shift_verbose",
-                   "sourcepath", "xpg_echo"];
+                   "sourcepath", "syntax_highlight", "xpg_echo"];
 
         for opt in opt_strs {
             options.opts.insert(opt.to_string(), false);
         }
 
-        let true_list = ["extglob", "progcomp"];
+        let true_list = ["extglob", "progcomp", "promptvars"];
         for opt in true_list {
             options.opts.insert(opt.to_string(), true);
         }
@@ -82,12 +115,9 @@ impl Options {
             false => Self::format2,
         };
 
-        let mut list = self.opts.iter()
-                       .map(|opt| f(opt.0, *opt.1))
-                       .collect::<Vec<String>>();
-
+        let mut list = self.opts.iter().collect::<Vec<(&String, &bool)>>();
         list.sort();
-        list.iter().for_each(|e| println!("{}", e));
+        list.iter().for_each(|opt| println!("{}", f(opt.0, *opt.1)));
     }
 
     pub fn print_if(&self, onoff: bool) {
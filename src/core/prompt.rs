@@ -0,0 +1,294 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{file_check, Feeder, Script, ShellCore};
+use crate::elements::subword;
+use crate::elements::subword::Subword;
+use crate::elements::subword::parameter::Parameter;
+use crate::elements::subword::simple::SimpleSubword;
+use crate::elements::word::{substitution, Word};
+use crate::utils::{clock, file};
+use std::fs;
+use std::path::Path;
+use nix::unistd;
+use nix::unistd::User;
+
+/* marks for \[ and \] left in an expanded prompt */
+pub const NONPRINT_BEGIN: char = '\x01';
+pub const NONPRINT_END: char = '\x02';
+
+fn get_branch(cwd: &str) -> String {
+    let mut dirs: Vec<&str> = cwd.split("/").collect();
+    while ! dirs.is_empty() {
+        let path = dirs.join("/") + "/.git/HEAD";
+        dirs.pop();
+
+        if ! file_check::is_regular_file(&path) {
+            continue;
+        }
+
+        return match fs::read_to_string(Path::new(&path)) {
+            Ok(s) => s.lines().next().unwrap_or("")
+                       .replace("ref: refs/heads/","") + "🌵",
+            _ => "".to_string(),
+        };
+    }
+
+    "".to_string()
+}
+
+fn user_name() -> String {
+    match User::from_uid(unistd::getuid()) {
+        Ok(Some(u)) => u.name,
+        _ => "".to_string(),
+    }
+}
+
+fn home_dir() -> String {
+    match User::from_uid(unistd::getuid()) {
+        Ok(Some(u)) => file::buf_to_name(&u.dir),
+        _ => "".to_string(),
+    }
+}
+
+fn host_name() -> String {
+    match unistd::gethostname() {
+        Ok(h) => file::oss_to_name(&h),
+        _ => "".to_string(),
+    }
+}
+
+fn tty_name() -> String {
+    match fs::read_link("/proc/self/fd/0") {
+        Ok(p) if p.starts_with("/dev") => {
+            let name = file::buf_to_name(&p);
+            name.rsplit('/').next().unwrap_or("tty").to_string()
+        },
+        _ => "tty".to_string(),
+    }
+}
+
+fn cwd() -> String {
+    match unistd::getcwd() {
+        Ok(p) => file::buf_to_name(&p),
+        _ => "".to_string(),
+    }
+}
+
+fn current_dir(basename: bool) -> String {
+    let cwd = cwd();
+    let home = home_dir();
+
+    if ! home.is_empty() && (cwd == home || cwd.starts_with(&(home.clone() + "/"))) {
+        if basename && cwd == home {
+            return "~".to_string();
+        }
+        if ! basename {
+            return cwd.replacen(&home, "~", 1);
+        }
+    }
+
+    match (basename, cwd.rsplit('/').next()) {
+        (true, Some(b)) if ! b.is_empty() => b.to_string(),
+        _ => cwd,
+    }
+}
+
+fn octal(chars: &[char]) -> Option<char> {
+    if chars.len() < 3 || ! chars[..3].iter().all(|c| ('0'..='7').contains(c)) {
+        return None;
+    }
+    let s: String = chars[..3].iter().collect();
+    u32::from_str_radix(&s, 8).ok().and_then(char::from_u32)
+}
+
+impl ShellCore {
+    fn prompt_escape(&mut self, c: char, chars: &[char], pos: &mut usize) -> Option<String> {
+        let now = clock::epoch_seconds();
+        let version = env!("CARGO_PKG_VERSION");
+
+        let s = match c {
+            'a' => "\x07".to_string(),
+            'b' => get_branch(&cwd()),
+            'd' => clock::strftime("%a %b %d", now),
+            'D' => {
+                let rest: String = chars[*pos..].iter().collect();
+                if ! rest.starts_with('{') || ! rest.contains('}') {
+                    return None;
+                }
+                let fmt: String = rest[1..].chars().take_while(|c| *c != '}').collect();
+                *pos += fmt.chars().count() + 2;
+                match fmt.is_empty() {
+                    true  => clock::strftime("%X", now),
+                    false => clock::strftime(&fmt, now),
+                }
+            },
+            'e' => "\x1b".to_string(),
+            'h' => host_name().split('.').next().unwrap_or("").to_string(),
+            'H' => host_name(),
+            'j' => self.job_table.len().to_string(),
+            'l' => tty_name(),
+            'n' => "\n".to_string(),
+            'r' => "\r".to_string(),
+            's' => {
+                let name = self.db.get_param("0").unwrap_or_default();
+                name.rsplit('/').next().unwrap_or("").to_string()
+            },
+            't' => clock::strftime("%H:%M:%S", now),
+            'T' => clock::strftime("%I:%M:%S", now),
+            '@' => clock::strftime("%I:%M %p", now),
+            'A' => clock::strftime("%H:%M", now),
+            'u' => user_name(),
+            'v' => version.rsplitn(2, '.').last().unwrap_or(version).to_string(),
+            'V' => version.to_string(),
+            'w' => current_dir(false),
+            'W' => current_dir(true),
+            '!' => (self.history.len() + 1).to_string(),
+            '#' => self.command_number.to_string(),
+            '$' => match unistd::geteuid().is_root() {
+                true  => "#".to_string(),
+                false => "$".to_string(),
+            },
+            '[' => NONPRINT_BEGIN.to_string(),
+            ']' => NONPRINT_END.to_string(),
+            '\\' => "\\".to_string(),
+            _ => {
+                let c = octal(&chars[*pos-1..])?;
+                *pos += 2;
+                c.to_string()
+            },
+        };
+        Some(s)
+    }
+
+    /* decodes backslash escapes; the results are protected from promptvars expansion */
+    fn decode_prompt(&mut self, raw: &str, promptvars: bool) -> String {
+        let chars: Vec<char> = raw.chars().collect();
+        let mut ans = String::new();
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            let c = chars[pos];
+            pos += 1;
+            if c != '\\' || pos == chars.len() {
+                ans.push(c);
+                continue;
+            }
+
+            let e = chars[pos];
+            pos += 1;
+            match self.prompt_escape(e, &chars, &mut pos) {
+                Some(s) if promptvars => {
+                    for c in s.chars() {
+                        if c == '$' || c == '`' || c == '\\' {
+                            ans.push('\\');
+                        }
+                        ans.push(c);
+                    }
+                },
+                Some(s) => ans += &s,
+                None => {
+                    ans.push('\\');
+                    ans.push(e);
+                },
+            }
+        }
+        ans
+    }
+
+    fn expand_prompt_vars(&mut self, decoded: &str) -> String {
+        let mut subwords: Vec<Box<dyn Subword>> = vec![];
+        let mut literal = String::new();
+        let mut rest = decoded;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '\\' && rest[1..].starts_with(['$', '`', '\\']) {
+                literal.push(rest.chars().nth(1).unwrap());
+                rest = &rest[2..];
+                continue;
+            }
+            if c != '$' {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+
+            let mut feeder = Feeder::new(&rest[1..]);
+            let name_len = feeder.scanner_name(self);
+            let sw: Box<dyn Subword> = match name_len {
+                0 => {
+                    feeder = Feeder::new(rest);
+                    match subword::parse(&mut feeder, self) {
+                        Ok(Some(sw)) if sw.get_text() != "$" => sw,
+                        _ => {
+                            literal.push('$');
+                            rest = &rest[1..];
+                            continue;
+                        },
+                    }
+                },
+                n => Box::new(Parameter{ text: rest[..n+1].to_string() }),
+            };
+
+            let consumed = match name_len {
+                0 => rest.len() - feeder.len(),
+                n => n + 1,
+            };
+            subwords.push(Box::new(SimpleSubword{ text: std::mem::take(&mut literal) }));
+            subwords.push(sw);
+            rest = &rest[consumed..];
+        }
+        subwords.push(Box::new(SimpleSubword{ text: literal }));
+
+        let mut word = Word::from(subwords);
+        match substitution::eval(&mut word, self) {
+            Ok(()) => word.subwords.iter().map(|s| s.get_text()).collect(),
+            Err(_) => word.text,
+        }
+    }
+
+    /* the value of a prompt variable after decoding and promptvars expansion */
+    pub fn expand_prompt(&mut self, name: &str) -> String {
+        let raw = self.db.get_param(name).unwrap_or_default();
+        self.expand_prompt_string(&raw)
+    }
+
+    pub fn expand_prompt_string(&mut self, raw: &str) -> String {
+        let promptvars = self.shopts.query("promptvars");
+        let decoded = self.decode_prompt(raw, promptvars);
+        match promptvars {
+            true  => self.expand_prompt_vars(&decoded),
+            false => decoded,
+        }
+    }
+
+    pub fn run_prompt_command(&mut self) {
+        let commands = match self.db.is_array("PROMPT_COMMAND") {
+            true  => self.db.get_array_all("PROMPT_COMMAND"),
+            false => match self.db.get_param("PROMPT_COMMAND") {
+                Ok(c) if ! c.is_empty() => vec![c],
+                _ => return,
+            },
+        };
+
+        let exit_status = self.db.exit_status;
+        for command in commands {
+            let mut feeder = Feeder::new(&(command + "\n"));
+            match Script::parse(&mut feeder, self, false) {
+                Ok(Some(mut s)) => {let _ = s.exec(self); },
+                Err(e) => e.print(self),
+                _ => {},
+            }
+        }
+        self.db.exit_status = exit_status;
+    }
+
+    pub fn print_ps0(&mut self) {
+        if ! self.db.has_value("PS0") {
+            return;
+        }
+        let ps0 = self.expand_prompt("PS0")
+                  .replace([NONPRINT_BEGIN, NONPRINT_END], "");
+        eprint!("{}", ps0);
+    }
+}
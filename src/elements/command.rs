@@ -12,7 +12,8 @@ pub mod function_def;
 pub mod r#while;
 pub mod r#if;
 
-use crate::{proc_ctrl, ShellCore, Feeder, Script};
+use crate::{proc_ctrl, utils, ShellCore, Feeder, Script};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::utils::exit;
@@ -100,7 +101,10 @@ pub fn eat_inner_script(feeder: &mut Feeder, core: &mut ShellCore, left: &str, r
         return Ok(false);
     }
     feeder.nest.push( (left.to_string(), right.iter().map(|e| e.to_string()).collect()) );
-    feeder.consume(left.len());
+    match utils::reserved(left) {
+        true  => feeder.consume_token(left.len(), Token::Keyword),
+        false => feeder.consume(left.len()),
+    };
     let result_script = Script::parse(feeder, core, permit_empty);
     feeder.nest.pop();
     *ans = result_script?;
@@ -115,7 +119,7 @@ pub fn eat_blank_with_comment(feeder: &mut Feeder, core: &mut ShellCore, ans_tex
     *ans_text += &feeder.consume(blank_len);
 
     let comment_len = feeder.scanner_comment();
-    *ans_text += &feeder.consume(comment_len);
+    *ans_text += &feeder.consume_token(comment_len, Token::Comment);
     true
 }
 
@@ -137,27 +141,56 @@ fn eat_blank_lines(feeder: &mut Feeder, core: &mut ShellCore, ans_text: &mut Str
 }
 
 fn eat_redirect(feeder: &mut Feeder, core: &mut ShellCore,
-                     ans: &mut Vec<Redirect>, ans_text: &mut String) -> bool {
-    if let Some(r) = Redirect::parse(feeder, core) {
+                     ans: &mut Vec<Redirect>, ans_text: &mut String) -> Result<bool, ParseError> {
+    if let Some(r) = Redirect::parse(feeder, core)? {
         *ans_text += &r.text.clone();
         ans.push(r);
-        true
+        Ok(true)
     }else{
-        false
+        Ok(false)
     }
 }
 
 pub fn eat_redirects(feeder: &mut Feeder, core: &mut ShellCore,
-                     ans_redirects: &mut Vec<Redirect>, ans_text: &mut String) {
+                     ans_redirects: &mut Vec<Redirect>, ans_text: &mut String) -> Result<(), ParseError> {
     loop {
         eat_blank_with_comment(feeder, core, ans_text);
-        if ! eat_redirect(feeder, core, ans_redirects, ans_text){
-            break;
+        if ! eat_redirect(feeder, core, ans_redirects, ans_text)? {
+            return Ok(());
         }
     }
 }
 
+/* the word opening a compound command and the one closing it */
+fn terminator(feeder: &Feeder) -> Option<(&'static str, &'static str)> {
+    const PAIRS: [(&str, &str); 6] = [("if", "fi"), ("for", "done"), ("while", "done"),
+                                      ("case", "esac"), ("{", "}"), ("(", ")")];
+    PAIRS.into_iter().find(|(open, _)| {
+        feeder.starts_with(open) && match feeder.nth(open.len()) {
+            Some(c) => ! open.starts_with(char::is_alphabetic) || " \t\n;&|()<>".contains(c),
+            None    => true,
+        }
+    })
+}
+
+/* an EOF in a compound command is reported with the word opening it */
+pub fn parse_closed<T>(feeder: &mut Feeder, core: &mut ShellCore,
+                       parse: impl FnOnce(&mut Feeder, &mut ShellCore) -> Result<T, ParseError>)
+                       -> Result<T, ParseError> {
+    match terminator(feeder) {
+        Some((open, term)) => {
+            let pos = feeder.pos(open.len());
+            parse(feeder, core).map_err(|e| e.in_construct(open, term, pos))
+        },
+        None => parse(feeder, core),
+    }
+}
+
 pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Box<dyn Command>>, ParseError> {
+    parse_closed(feeder, core, parse_command)
+}
+
+fn parse_command(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Box<dyn Command>>, ParseError> {
     if let Some(a) = FunctionDefinition::parse(feeder, core)? { Ok(Some(Box::new(a))) }
     else if let Some(a) = SimpleCommand::parse(feeder, core)? { Ok(Some(Box::new(a))) }
     else if let Some(a) = IfCommand::parse(feeder, core)? { Ok(Some(Box::new(a))) }
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder, Script};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::utils::exit;
@@ -39,9 +40,9 @@ impl BraceCommand {
         if command::eat_inner_script(feeder, core, "{", vec!["}"], &mut ans.script, false)? {
             ans.text.push_str("{");
             ans.text.push_str(&ans.script.as_ref().unwrap().get_text());
-            ans.text.push_str(&feeder.consume(1));
+            ans.text.push_str(&feeder.consume_token(1, Token::Keyword));
 
-            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
             Ok(Some(ans))
         }else{
             Ok(None)
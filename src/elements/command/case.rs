@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder, Script};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::elements::command;
@@ -34,6 +35,10 @@ impl Command for CaseCommand {
         };
 
         let extglob = core.shopts.query("extglob");
+        let compare = match core.shopts.query("nocasematch") {
+            true  => glob::parse_and_compare_nocase,
+            false => glob::parse_and_compare,
+        };
 
         for e in &mut self.patterns_script_end {
             for pattern in &mut e.0 {
@@ -42,7 +47,7 @@ impl Command for CaseCommand {
                     _       => continue,
                 };
 
-                if glob::parse_and_compare(&w, &p, extglob) || next {
+                if compare(&w, &p, extglob) || next {
                     let _ = e.1.exec(core);
 
                     if e.2 == ";;" {
@@ -124,13 +129,13 @@ impl CaseCommand {
         }
 
         let mut ans = Self::new();
-        ans.text = feeder.consume(4);
+        ans.text = feeder.consume_token(4, Token::Keyword);
 
         if ! Self::eat_word(feeder, &mut ans, core)?
         || ! feeder.starts_with("in") {
             return Ok(None);
         }
-        ans.text += &feeder.consume(2);
+        ans.text += &feeder.consume_token(2, Token::Keyword);
 
         loop {
             command::eat_blank_lines(feeder, core, &mut ans.text)?;
@@ -157,9 +162,9 @@ impl CaseCommand {
         }
 
         if feeder.starts_with("esac") {
-            ans.text += &feeder.consume(4);
+            ans.text += &feeder.consume_token(4, Token::Keyword);
             if ans.patterns_script_end.len() > 0 {
-                command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+                command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
                 return Ok(Some(ans));
             }
         }
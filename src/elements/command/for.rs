@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder, Script};
+use crate::feeder::Token;
 
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
@@ -198,7 +199,7 @@ impl ForCommand {
             return Ok(());
         }
 
-        ans.text += &feeder.consume(2);
+        ans.text += &feeder.consume_token(2, Token::Keyword);
         ans.has_in = true;
 
         loop {
@@ -230,7 +231,7 @@ impl ForCommand {
             return Ok(None);
         }
         let mut ans = Self::default();
-        ans.text = feeder.consume(3);
+        ans.text = feeder.consume_token(3, Token::Keyword);
 
         if Self::eat_name(feeder, &mut ans, core) {
             Self::eat_in_part(feeder, &mut ans, core)?;
@@ -247,9 +248,9 @@ impl ForCommand {
         if command::eat_inner_script(feeder, core, "do", vec!["done"],  &mut ans.do_script, false)? {
             ans.text.push_str("do");
             ans.text.push_str(&ans.do_script.as_mut().unwrap().get_text());
-            ans.text.push_str(&feeder.consume(4)); //done
+            ans.text.push_str(&feeder.consume_token(4, Token::Keyword)); //done
 
-            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
             Ok(Some(ans))
         }else{
             Ok(None)
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use super::{Command, Pipe, Redirect};
@@ -20,6 +21,7 @@ fn reserved(w: &str) -> bool {
 pub struct FunctionDefinition {
     pub text: String,
     name: String,
+    source: String,
     command: Option<Box<dyn Command>>,
     redirects: Vec<Redirect>,
     force_fork: bool,
@@ -31,6 +33,10 @@ impl Command for FunctionDefinition {
             return Ok(None);
         }
 
+        self.source = match core.db.get_array_elem("BASH_SOURCE", "0") {
+            Ok(s) if ! s.is_empty() => s,
+            _ => "main".to_string(),
+        };
         core.db.functions.insert(self.name.to_string(), self.clone());
         Ok(None)
     }
@@ -48,6 +54,7 @@ impl FunctionDefinition {
         FunctionDefinition {
             text: String::new(),
             name: String::new(),
+            source: String::new(),
             command: None,
             redirects: vec![],
             force_fork: false,
@@ -56,9 +63,8 @@ impl FunctionDefinition {
 
     pub fn run_as_command(&mut self, args: &mut Vec<String>, core: &mut ShellCore)
         -> Result<Option<Pid>, ExecError> {
-        let mut array = core.db.get_array_all("FUNCNAME");
-        array.insert(0, args[0].clone());
-        let _ = core.db.set_array("FUNCNAME", array, None);
+        core.db.push_front("FUNCNAME", vec![args[0].clone()]);
+        let with_args = core.push_call_stack(&self.source, &args[1..]);
 
         let len = core.db.position_parameters.len();
         args[0] = core.db.position_parameters[len-1][0].clone();
@@ -76,9 +82,8 @@ impl FunctionDefinition {
         core.db.position_parameters.pop();
 
         //core.db.set_param("#", &number);, None, None
-        let mut array = core.db.get_array_all("FUNCNAME");
-        array.remove(0);
-        let _ = core.db.set_array("FUNCNAME", array, None);
+        core.db.pop_front("FUNCNAME", 1);
+        core.pop_call_stack(with_args);
         pid
     }
 
@@ -116,7 +121,7 @@ impl FunctionDefinition {
         feeder.set_backup();
 
         if feeder.starts_with("function") {
-            ans.text += &feeder.consume(8);
+            ans.text += &feeder.consume_token(8, Token::Keyword);
             command::eat_blank_with_comment(feeder, core, &mut ans.text);
         }
         
@@ -140,7 +145,7 @@ impl FunctionDefinition {
             }
         }
 
-        Self::eat_compound_command(feeder, &mut ans, core)?;
+        command::parse_closed(feeder, core, |f, c| Self::eat_compound_command(f, &mut ans, c))?;
         command::eat_blank_with_comment(feeder, core, &mut ans.text);
 
         if let Some(_) = &ans.command {
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder, Script};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::elements::command;
@@ -21,7 +22,10 @@ pub struct IfCommand {
 impl Command for IfCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
         for i in 0..self.if_elif_scripts.len() {
+            let susp_e_option = core.suspend_e_option;
+            core.suspend_e_option = true;
             let _ = self.if_elif_scripts[i].exec(core);
+            core.suspend_e_option = susp_e_option;
             if core.db.exit_status == 0 {
                 let _ = self.then_scripts[i].exec(core);
                 return Ok(());
@@ -85,7 +89,7 @@ impl IfCommand {
             Self::eat_word_and_script("else", feeder, &mut ans, core)?; //optional
 
             if feeder.starts_with("fi") { // If "else" exists, always it comes here.
-                ans.text.push_str(&feeder.consume(2));
+                ans.text.push_str(&feeder.consume_token(2, Token::Keyword));
                 break;
             }
 
@@ -96,7 +100,7 @@ impl IfCommand {
             return Ok(None);
         }
 
-        command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+        command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
         Ok(Some(ans))
     }
 }
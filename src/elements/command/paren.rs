@@ -14,6 +14,7 @@ pub struct ParenCommand {
     text: String,
     script: Option<Script>,
     redirects: Vec<Redirect>,
+    substitution: bool,
 }
 
 impl Command for ParenCommand {
@@ -26,6 +27,10 @@ impl Command for ParenCommand {
             exit::internal(" (no fork for subshell)");
         }
 
+        if self.substitution && ! core.shopts.query("inherit_errexit") {
+            core.db.flags.retain(|f| f != 'e');
+        }
+
         match self.script {
             Some(ref mut s) => s.exec(core)?,
             _ => exit::internal(" (ParenCommand::exec)"),
@@ -43,14 +48,17 @@ impl Command for ParenCommand {
 impl ParenCommand {
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore, substitution: bool)
         -> Result<Option<Self>, ParseError> {
-        let mut ans = Self::default();
+        let mut ans = Self {
+            substitution,
+            ..Default::default()
+        };
         if command::eat_inner_script(feeder, core, "(", vec![")"], &mut ans.script, substitution)? {
             ans.text.push_str("(");
             ans.text.push_str(&ans.script.as_ref().unwrap().get_text());
             ans.text.push_str(&feeder.consume(1));
 
             if ! substitution {
-                command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+                command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
             }
             Ok(Some(ans))
         }else{
@@ -30,6 +30,7 @@ pub struct SimpleCommand {
 impl Command for SimpleCommand {
     fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Result<Option<Pid>, ExecError> {
         let _ = core.db.set_param("LINENO", &self.lineno.to_string(), None);
+        let _ = core.db.set_param("BASH_COMMAND", self.text.trim_end(), None);
         if Self::break_continue_or_return(core) {
             return Ok(None);
         }
@@ -65,6 +66,7 @@ impl Command for SimpleCommand {
         }
 
         core.db.pop_local();
+        core.e_failure_suspended = false;
 
         if fork {
             exit::normal(core);
@@ -113,7 +115,8 @@ impl SimpleCommand {
     fn exec_set_param(&mut self, core: &mut ShellCore) -> Result<Option<Pid>, ExecError> {
         core.db.last_arg = String::new();
         self.option_x_output(core);
-        
+        core.db.exit_status = 0;
+
         self.substitutions.iter_mut()
             .for_each(|s| {let _ = s.eval(core, None, false);});
 
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder, utils};
+use crate::feeder::Token;
 use super::SimpleCommand;
 use crate::elements::command;
 use crate::elements::substitution::Substitution;
@@ -24,6 +25,7 @@ impl SimpleCommand {
 
     fn eat_word(feeder: &mut Feeder, ans: &mut SimpleCommand, core: &mut ShellCore)
         -> Result<bool, ParseError> {
+        let start = feeder.offset();
         let w = match Word::parse(feeder, core, false) {
             Ok(Some(w)) => w,
             Err(e) => {
@@ -44,7 +46,9 @@ impl SimpleCommand {
         }
 
         if ans.words.is_empty() {
-            if Self::set_alias(&w, &mut ans.words, &mut ans.text, core, feeder) {
+            feeder.mark(start, Token::Command);
+            if feeder.tokens.is_none() //an alias would move the recorded positions
+            && Self::set_alias(&w, &mut ans.words, &mut ans.text, core, feeder) {
                 return Ok(true);
             }
         }
@@ -88,13 +92,14 @@ impl SimpleCommand {
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
         let mut ans = Self::default();
         feeder.set_backup();
+        ans.lineno = feeder.lineno;
 
         while Self::eat_substitution(feeder, &mut ans, core)? {
             command::eat_blank_with_comment(feeder, core, &mut ans.text);
         }
 
         loop {
-            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
             if ans.permit_substitution_arg 
             && Self::eat_substitution(feeder, &mut ans, core)? {
                 continue;
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use super::{Command, Redirect};
@@ -49,7 +50,7 @@ impl TestCommand {
         }
 
         let mut ans = Self::default();
-        ans.text = feeder.consume(2);
+        ans.text = feeder.consume_token(2, Token::Keyword);
 
         match ConditionalExpr::parse(feeder, core) {
             Some(e) => {
@@ -60,8 +61,8 @@ impl TestCommand {
         }
 
         if feeder.starts_with("]]") {
-            ans.text += &feeder.consume(2);
-            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            ans.text += &feeder.consume_token(2, Token::Keyword);
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
             return Ok(Some(ans));
         }
     
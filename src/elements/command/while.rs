@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder, Script};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use super::{Command, Redirect};
@@ -19,11 +20,12 @@ pub struct WhileCommand {
 impl Command for WhileCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
         core.loop_level += 1;
+        let susp_e_option = core.suspend_e_option;
         loop {
             core.suspend_e_option = true;
             let _ = self.while_script.as_mut().unwrap().exec(core);
 
-            core.suspend_e_option = false;
+            core.suspend_e_option = susp_e_option;
             if core.db.exit_status != 0 {
                 core.db.exit_status = 0;
                 break;
@@ -70,9 +72,9 @@ impl WhileCommand {
             ans.text.push_str(&ans.while_script.as_mut().unwrap().get_text());
             ans.text.push_str("do");
             ans.text.push_str(&ans.do_script.as_mut().unwrap().get_text());
-            ans.text.push_str(&feeder.consume(4)); //done
+            ans.text.push_str(&feeder.consume_token(4, Token::Keyword)); //done
 
-            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text);
+            command::eat_redirects(feeder, core, &mut ans.redirects, &mut ans.text)?;
             Ok(Some(ans))
         }else{
             Ok(None)
@@ -50,10 +50,13 @@ pub fn str_to_num(name: &str, core: &mut ShellCore) -> Result<ArithElem, ExecErr
     const RESOLVE_LIMIT: i32 = 10000;
 
     for i in 0..RESOLVE_LIMIT {
-        match utils::is_name(&name, core) {
-            true  => name = core.db.get_param(&name)?,
-            false => break,
+        if ! utils::is_name(&name, core) {
+            break;
         }
+        if core.db.flags.contains('u') && core.db.is_unset(&name) {
+            return Err(exit::unbound_variable(&name, core));
+        }
+        name = core.db.get_param(&name)?;
 
         if i == RESOLVE_LIMIT - 1 {
             return Err(ExecError::Recursion(name.clone()));
@@ -5,10 +5,10 @@ pub mod elem;
 mod parser;
 
 use crate::ShellCore;
+use crate::core::builtins::option;
 use crate::error::exec::ExecError;
-use crate::utils::{file_check, glob};
+use crate::utils::{ere, file_check, glob};
 use crate::elements::word::Word;
-use regex::Regex;
 use self::elem::CondElem;
 use super::arithmetic::word;
 use super::arithmetic::elem::ArithElem;
@@ -156,7 +156,7 @@ impl ConditionalExpr {
 
         if op == "-o" || op == "-v" || op == "-z" || op == "-n" {
             let ans = match op {
-                "-o" => core.options.query(&operand),
+                "-o" => option::query_option(core, &operand),
                 //"-v" => core.db.get_value(&operand).is_some() || env::var(&operand).is_ok(),
                 "-v" => core.db.has_value(&operand) || env::var(&operand).is_ok(),
                 "-z" => operand.is_empty(),
@@ -178,10 +178,11 @@ impl ConditionalExpr {
             Err(e) => return Err(ExecError::Other(e)),
         };
 
-        let left = match pop_operand(stack, core) {
-            Ok(CondElem::Operand(name)) => name,
-            Ok(_)  => return Err(ExecError::Other("Invalid operand".to_string())),
-            Err(e) => return Err(ExecError::Other(e)),
+        let left = match stack.pop() {
+            Some(CondElem::Word(w)) => w.tilde_and_dollar_expansion(core)?
+                                         .make_unquoted_word().unwrap_or_default(), //empty when nothing is left
+            Some(CondElem::Operand(name)) => name,
+            _ => return Err(ExecError::Other("Invalid operand".to_string())),
         };
 
         let right_eval = match right.eval_for_case_pattern(core) {
@@ -189,12 +190,15 @@ impl ConditionalExpr {
             None  => return Err(ExecError::Other("Invalid operand".to_string())),
         };
 
-        let re = match Regex::new(&right_eval) {
-            Ok(regex) => regex,
-            Err(e) => return Err(ExecError::Other(e.to_string())),
+        let nocase = core.shopts.query("nocasematch");
+        let groups = match ere::captures(&left, &right_eval, nocase) {
+            Ok(g) => g,
+            Err(e) => return Err(ExecError::Other(e)),
         };
 
-        stack.push( CondElem::Ans(re.is_match(&left)) );
+        let ans = groups.is_some();
+        core.db.set_array("BASH_REMATCH", groups.unwrap_or_default(), None)?;
+        stack.push( CondElem::Ans(ans) );
         return Ok(());
     }
 
@@ -213,11 +217,15 @@ impl ConditionalExpr {
         };
 
         let extglob = core.shopts.query("extglob");
+        let compare = match core.shopts.query("nocasematch") {
+            true  => glob::parse_and_compare_nocase,
+            false => glob::parse_and_compare,
+        };
         if op.starts_with("=") || op == "!=" || op == "<" || op == ">" {
             let ans = match op {
-                "==" | "=" => glob::parse_and_compare(&left, &right, extglob),
-                "=~"       => glob::parse_and_compare(&left, &right, extglob),
-                "!="       => ! glob::parse_and_compare(&left, &right, extglob),
+                "==" | "=" => compare(&left, &right, extglob),
+                "=~"       => compare(&left, &right, extglob),
+                "!="       => ! compare(&left, &right, extglob),
                 ">"        => left > right,
                 "<"        => left < right,
                 _    => false,
@@ -3,10 +3,12 @@
 
 use std::fs::{File, OpenOptions};
 use std::os::fd::{IntoRawFd, RawFd};
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use crate::elements::io;
 use crate::elements::word::Word;
 use crate::{Feeder, ShellCore};
+use crate::feeder::Token;
+use crate::error::parse::ParseError;
 use crate::utils::exit;
 use nix::unistd;
 use nix::unistd::ForkResult;
@@ -25,6 +27,16 @@ pub struct Redirect {
     extra_left_backup: RawFd, // &>, &>>用
 }
 
+/* the token after a redirection symbol, which cannot be its target */
+fn next_token(feeder: &mut Feeder, core: &mut ShellCore) -> String {
+    let lens = [feeder.scanner_and_or(core), feeder.scanner_pipe(core),
+                feeder.scanner_redirect_symbol(core), feeder.scanner_job_end()];
+    match lens.into_iter().find(|n| *n > 0) {
+        Some(n) => feeder.refer(n).to_string(),
+        None    => feeder.refer(feeder.len()).split(' ').next().unwrap_or("").to_string(),
+    }
+}
+
 impl Redirect {
     pub fn connect(&mut self, restore: bool, core: &mut ShellCore) -> bool {
         if self.symbol == "<<<" {
@@ -46,12 +58,14 @@ impl Redirect {
 
         self.right.text = args[0].clone();
 
+        let noclobber = core.db.flags.contains('C');
         match self.symbol.as_str() {
             "<" => self.redirect_simple_input(restore),
-            ">" => self.redirect_simple_output(restore),
+            ">" => self.redirect_simple_output(restore, noclobber),
+            ">|" => self.redirect_simple_output(restore, false),
             ">&" => self.redirect_output_fd(restore),
             ">>" => self.redirect_append(restore),
-            "&>" => self.redirect_both_output(restore),
+            "&>" => self.redirect_both_output(restore, noclobber),
             _ => exit::internal(" (Unknown redirect symbol)"),
         }
     }
@@ -78,6 +92,10 @@ impl Redirect {
                 }
                 result
             },
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                eprintln!("sush: {}: cannot overwrite existing file", &self.right.text);
+                false
+            },
             _  => {
                 eprintln!("sush: {}: {}", &self.right.text, Error::last_os_error().kind());
                 false
@@ -90,9 +108,34 @@ impl Redirect {
         self.connect_to_file(File::open(&self.right.text), restore)
     }
 
-    fn redirect_simple_output(&mut self, restore: bool) -> bool {
+    /* O_EXCL avoids a race between the existence check and the creation;
+     * a file which is not regular such as /dev/null can be opened */
+    fn create_noclobber(&self) -> Result<File, Error> {
+        match OpenOptions::new().write(true).create_new(true).open(&self.right.text) {
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let file = match OpenOptions::new().write(true).open(&self.right.text) {
+                    Ok(f) => f,
+                    Err(_) => return Err(e), //e.g. a dangling symlink
+                };
+                match file.metadata()?.is_file() {
+                    true  => Err(e),
+                    false => Ok(file),
+                }
+            },
+            result => result,
+        }
+    }
+
+    fn create(&self, noclobber: bool) -> Result<File, Error> {
+        match noclobber {
+            true  => self.create_noclobber(),
+            false => File::create(&self.right.text),
+        }
+    }
+
+    fn redirect_simple_output(&mut self, restore: bool, noclobber: bool) -> bool {
         self.set_left_fd(1);
-        self.connect_to_file(File::create(&self.right.text), restore)
+        self.connect_to_file(self.create(noclobber), restore)
     }
 
     fn redirect_output_fd(&mut self, _: bool) -> bool {
@@ -111,9 +154,9 @@ impl Redirect {
                 .write(true).append(true).open(&self.right.text), restore)
     }
 
-    fn redirect_both_output(&mut self, restore: bool) -> bool {
+    fn redirect_both_output(&mut self, restore: bool, noclobber: bool) -> bool {
         self.left_fd = 1;
-        if ! self.connect_to_file(File::create(&self.right.text), restore){
+        if ! self.connect_to_file(self.create(noclobber), restore){
             return false;
         }
 
@@ -180,18 +223,23 @@ impl Redirect {
         true
     }
 
-    fn eat_right(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+    fn eat_right(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> Result<(), ParseError> {
         let blank_len = feeder.scanner_blank(core);
         ans.text += &feeder.consume(blank_len);
 
-        let w = match Word::parse(feeder, core, false) {
-            Ok(Some(w)) => w,
-            _       => return false,
+        let w = match Word::parse(feeder, core, false)? {
+            Some(w) => w,
+            None    => {
+                let token = next_token(feeder, core);
+                let pos = feeder.pos(std::cmp::max(token.trim_end().chars().count(), 1));
+                core.db.exit_status = 2;
+                return Err(ParseError::BadRedirect(token, pos));
+            },
         };
 
         ans.text += &w.text.clone();
         ans.right = w;
-        true
+        Ok(())
     }
 
     fn eat_left(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
@@ -209,18 +257,20 @@ impl Redirect {
         }
     }
 
-    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Redirect> {
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Redirect>, ParseError> {
         let mut ans = Self::new();
+        let start = feeder.offset();
         feeder.set_backup(); //追加
 
-        if Self::eat_left(feeder, &mut ans, core) &&
-           Self::eat_symbol(feeder, &mut ans, core) &&
-           Self::eat_right(feeder, &mut ans, core) {
-            feeder.pop_backup();
-            Some(ans)
-        }else{
+        if ! Self::eat_left(feeder, &mut ans, core) ||
+           ! Self::eat_symbol(feeder, &mut ans, core) {
             feeder.rewind(); //追加
-            None
+            return Ok(None);
         }
+
+        feeder.pop_backup();
+        feeder.mark(start, Token::Redirect);
+        Self::eat_right(feeder, &mut ans, core)?;
+        Ok(Some(ans))
     }
 }
@@ -37,21 +37,26 @@ impl Job {
         let susp_e_option = core.suspend_e_option;
         for (pipeline, end) in self.pipelines.iter_mut().zip(self.pipeline_ends.iter()) {
 
-            core.suspend_e_option = susp_e_option || end == "&&" || end == "||";
+            core.suspend_e_option = susp_e_option || end == "&&" || end == "||"
+                                    || pipeline.exclamation;
             if do_next {
                 core.jobtable_check_status();
+                core.e_failure_suspended = false;
                 let (pids, exclamation, time, err) = pipeline.exec(core, pgid);
                 let waitstatuses = proc_ctrl::wait_pipeline(core, pids.clone(), exclamation, time);
+                exit::check_e_option(core);
 
                 Self::check_stop(core, &pipeline.text, &pids, &waitstatuses);
 
                 if err.is_some() {
+                    core.suspend_e_option = susp_e_option;
                     return Err(err.unwrap());
                 }
             }
 
             do_next = (core.db.exit_status == 0) == (end == "&&");
         }
+        core.suspend_e_option = susp_e_option;
         Ok(())
     }
 
@@ -18,7 +18,7 @@ pub struct Pipeline {
     pub commands: Vec<Box<dyn Command>>,
     pub pipes: Vec<Pipe>,
     pub text: String,
-    exclamation: bool,
+    pub exclamation: bool,
     pub time: bool,
 }
 
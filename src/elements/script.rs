@@ -3,11 +3,11 @@
 
 use super::job::Job;
 use crate::error::exec::ExecError;
-use crate::error::parse::ParseError;
+use crate::error::parse::{ParseError, SourcePos};
 use crate::{Feeder, ShellCore};
 
 enum Status{
-    UnexpectedSymbol(String),
+    UnexpectedSymbol(String, SourcePos),
     NeedMoreLine,
     NormalEnd,
 }
@@ -22,6 +22,9 @@ pub struct Script {
 impl Script {
     pub fn exec(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
         for (job, end) in self.jobs.iter_mut().zip(self.job_ends.iter()) {
+            if core.noexec() {
+                break;
+            }
             job.exec(core, end == "&")?;
         }
         Ok(())
@@ -63,16 +66,17 @@ impl Script {
                 if permit_empty {
                     return Status::NormalEnd;
                 }
-                return Status::UnexpectedSymbol(end.to_string())
+                return Status::UnexpectedSymbol(end.to_string(), feeder.pos(end.chars().count()))
             },
             ( Some(_), _)    => return Status::NormalEnd,
             ( None, _)       => {}, 
         }
 
         if feeder.len() > 0 {
-            let remaining = feeder.consume(feeder.len());
-            let first_token = remaining.split(" ").nth(0).unwrap().to_string();
-            return Status::UnexpectedSymbol(first_token);
+            let first_token = feeder.refer(feeder.len()).split(" ").nth(0).unwrap().to_string();
+            let pos = feeder.pos(first_token.trim_end_matches('\n').chars().count());
+            feeder.consume(feeder.len());
+            return Status::UnexpectedSymbol(first_token, pos);
         }
 
         Status::NeedMoreLine
@@ -103,10 +107,10 @@ impl Script {
                     return Ok(Some(ans))
                 },
                 Status::NeedMoreLine => feeder.feed_additional_line(core)?,
-                Status::UnexpectedSymbol(s) => { //unexpected symbol
+                Status::UnexpectedSymbol(s, pos) => { //unexpected symbol
                     let _ = core.db.set_param("LINENO", &feeder.lineno.to_string(), None);
                     core.db.exit_status = 2;
-                    return Err(ParseError::UnexpectedSymbol(s));
+                    return Err(ParseError::UnexpectedSymbol(s, pos));
                 },
             }
         }
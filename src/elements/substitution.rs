@@ -48,6 +48,8 @@ impl Substitution {
                 let ans = self.set_to_shell(core, layer);
                 if ! ans.is_ok() {
                     core.db.exit_status = 1;
+                }else if core.db.flags.contains('a') {
+                    let _ = self.set_to_env();
                 }
                 ans
             },
@@ -90,46 +90,22 @@ pub trait Subword {
     fn get_child_subwords(&self) -> Vec<Box<dyn Subword>> { vec![] }
 }
 
-fn replace_history_expansion(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
-    let len = feeder.scanner_history_expansion(core);
-    if len == 0 {
-        return false;
-    }
-
-    let history_len = core.history.len();
-    if history_len < 2 {
-        feeder.replace(len, "");
-        return true;
-    }
-
-    let mut his = String::new();
-    for h in &core.history[1..] {
-        let last = h.split(" ").last().unwrap();
-
-        if ! last.starts_with("!$") {
-            his = last.to_string();
-            break;
-        }
-    }
-
-    feeder.replace(len, &his);
-    true
-}
-
 pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Box<dyn Subword>>, ParseError> {
-    if replace_history_expansion(feeder, core) {
-        return parse(feeder, core);
-    }
-
+    let start = feeder.offset();
     if let Some(a) = BracedParam::parse(feeder, core)?{ Ok(Some(Box::new(a))) }
     else if let Some(a) = Arithmetic::parse(feeder, core)?{ Ok(Some(Box::new(a))) }
     else if let Some(a) = CommandSubstitution::parse(feeder, core)?{ Ok(Some(Box::new(a))) }
-    else if let Some(a) = SingleQuoted::parse(feeder, core){ Ok(Some(Box::new(a))) }
+    else if let Some(a) = SingleQuoted::parse(feeder, core)?{ Ok(Some(Box::new(a))) }
     else if let Some(a) = DoubleQuoted::parse(feeder, core)? { Ok(Some(Box::new(a))) }
     else if let Some(a) = ExtGlob::parse(feeder, core)? { Ok(Some(Box::new(a))) }
     else if let Some(a) = EscapedChar::parse(feeder, core){ Ok(Some(Box::new(a))) }
     else if let Some(a) = Parameter::parse(feeder, core){ Ok(Some(Box::new(a))) }
     else if let Some(a) = VarName::parse(feeder, core){ Ok(Some(Box::new(a))) }
-    else if let Some(a) = SimpleSubword::parse(feeder){ Ok(Some(Box::new(a))) }
+    else if let Some(a) = SimpleSubword::parse(feeder){
+        if a.text == "$" {
+            feeder.mark_dollar_name(start, core);
+        }
+        Ok(Some(Box::new(a)))
+    }
     else{ Ok(None) }
 }
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use crate::elements::command::arithmetic::ArithmeticCommand;
@@ -32,10 +33,12 @@ impl Arithmetic {
             return Ok(None);
         }
         feeder.set_backup();
+        let start = feeder.offset();
         let dl = feeder.consume(1);
 
         if let Some(a) = ArithmeticCommand::parse(feeder, core)? {
             feeder.pop_backup();
+            feeder.mark(start, Token::Variable);
             return Ok(Some(Arithmetic{ text: dl + &a.text.clone(), com: a}));
         }
         feeder.rewind();
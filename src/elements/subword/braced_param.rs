@@ -6,7 +6,9 @@ mod substr;
 mod remove;
 mod replace;
 
-use crate::{ShellCore, Feeder};
+use crate::{exit, ShellCore, Feeder};
+use crate::core::prompt::{NONPRINT_BEGIN, NONPRINT_END};
+use crate::feeder::Token;
 use crate::elements::subword;
 use crate::elements::subword::Subword;
 use crate::elements::subscript::Subscript;
@@ -41,6 +43,7 @@ pub struct BracedParam {
     is_array: bool,
     num: bool,
     indirect: bool,
+    prompt: bool, //${name@P}
 }
 
 impl Subword for BracedParam {
@@ -72,6 +75,10 @@ impl Subword for BracedParam {
             }
         }
 
+        if self.check_unset(core) && core.db.is_unset(&self.param.name) {
+            return Err(exit::unbound_variable(&self.param.name, core));
+        }
+
         let value = core.db.get_param(&self.param.name).unwrap_or_default();
         self.text = match self.num {
             true  => value.chars().count().to_string(),
@@ -111,9 +118,20 @@ impl BracedParam {
         Ok(())
     }
 
+    /* set -u except the ${x-} forms */
+    fn check_unset(&self, core: &mut ShellCore) -> bool {
+        core.db.flags.contains('u') && self.value_check.is_none()
+    }
+
     fn subscript_operation(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
         let index = self.param.subscript.clone().unwrap().eval(core, &self.param.name)?;
 
+        if index != "@" && index != "*" && self.check_unset(core)
+        && ! core.db.has_array_elem(&self.param.name, &index) {
+            let name = format!("{}[{}]", &self.param.name, &index);
+            return Err(exit::unbound_variable(&name, core));
+        }
+
         if core.db.is_assoc(&self.param.name) {
             return self.subscript_operation_assoc(core, &index);
         }
@@ -145,6 +163,9 @@ impl BracedParam {
             r.set(&mut self.text, core)?
         }else if let Some(r) = &self.replace {
             r.get_text(&self.text, core)?
+        }else if self.prompt {
+            core.expand_prompt_string(&self.text)
+                .replace([NONPRINT_BEGIN, NONPRINT_END], "")
         }else{
             self.text.clone()
         };
@@ -207,6 +228,15 @@ impl BracedParam {
         feeder.starts_with("}")
     }
 
+    fn eat_prompt_operator(feeder: &mut Feeder, ans: &mut Self) -> bool {
+        if ! feeder.starts_with("@P") {
+            return false;
+        }
+        ans.text += &feeder.consume(2);
+        ans.prompt = true;
+        true
+    }
+
     fn eat_unknown(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> Result<(), ParseError> {
         if feeder.len() == 0 {
             feeder.feed_additional_line(core)?;
@@ -230,8 +260,15 @@ impl BracedParam {
             return Ok(None);
         }
         let mut ans = Self::default();
+        let start = feeder.offset();
+        let pos = feeder.pos(2);
         ans.text += &feeder.consume(2);
+        Self::eat_inside(feeder, &mut ans, core).map_err(|e| e.in_construct("${", "}", pos))?;
+        feeder.mark(start, Token::Variable);
+        Ok(Some(ans))
+    }
 
+    fn eat_inside(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> Result<(), ParseError> {
         if feeder.starts_with("#") && ! feeder.starts_with("#}") {
             ans.num = true;
             ans.text += &feeder.consume(1);
@@ -240,19 +277,20 @@ impl BracedParam {
             ans.text += &feeder.consume(1);
         }
 
-        if Self::eat_param(feeder, &mut ans, core) {
-            Self::eat_subscript(feeder, &mut ans, core)?;
-            let _ = ValueCheck::eat(feeder, &mut ans, core)?
-                 || Substr::eat(feeder, &mut ans, core)
-                 || Remove::eat(feeder, &mut ans, core)?
-                 || Replace::eat(feeder, &mut ans, core)?;
+        if Self::eat_param(feeder, ans, core) {
+            Self::eat_subscript(feeder, ans, core)?;
+            let _ = ValueCheck::eat(feeder, ans, core)?
+                 || Substr::eat(feeder, ans, core)
+                 || Remove::eat(feeder, ans, core)?
+                 || Replace::eat(feeder, ans, core)?
+                 || Self::eat_prompt_operator(feeder, ans);
         }
 
         while ! feeder.starts_with("}") {
-            Self::eat_unknown(feeder, &mut ans, core)?;
+            Self::eat_unknown(feeder, ans, core)?;
         }
 
         ans.text += &feeder.consume(1);
-        Ok(Some(ans))
+        Ok(())
     }
 }
@@ -16,13 +16,10 @@ pub struct ValueCheck {
 impl ValueCheck {
     pub fn set(&mut self, name: &String, text: &String, core: &mut ShellCore) -> Result<String, ExecError> {
         match self.symbol.as_deref() {
-            Some(":-")   => {
-                self.set_alter_word(core)?;
-                Ok(text.clone())
-            },
+            Some(":-") => self.colon_minus(text, core),
             Some(":?") => self.colon_question(name, text, core),
             Some(":=") => self.colon_equal(name, core),
-            Some("-")  => self.minus(text),
+            Some("-")  => self.minus(name, text, core),
             Some(":+") => self.colon_plus(text, core),
             Some("+")  => self.plus(name, text, core),
             _          => exit::internal("no operation"),
@@ -36,9 +33,19 @@ impl ValueCheck {
         Ok(value.clone())
     }
 
-    fn minus(&mut self, text: &String) -> Result<String, ExecError> {
-        self.alternative_value = None;
-        self.symbol = None;
+    fn minus(&mut self, name: &String, text: &String, core: &mut ShellCore) -> Result<String, ExecError> {
+        match core.db.is_unset(&name) {
+            true  => {self.set_alter_word(core)?;},
+            false => self.alternative_value = None,
+        }
+        Ok(text.clone())
+    }
+
+    fn colon_minus(&mut self, text: &String, core: &mut ShellCore) -> Result<String, ExecError> {
+        match text.is_empty() {
+            true  => {self.set_alter_word(core)?;},
+            false => self.alternative_value = None,
+        }
         Ok(text.clone())
     }
 
@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{proc_ctrl, ShellCore, Feeder};
+use crate::feeder::Token;
 use crate::elements::Pipe;
 use crate::elements::command::Command;
 use crate::elements::command::paren::ParenCommand;
@@ -75,9 +76,19 @@ impl CommandSubstitution {
         if ! feeder.starts_with("$(") {
             return Ok(None);
         }
+        let start = feeder.offset();
+        let pos = feeder.pos(2);
+        let arith_pos = feeder.pos(3);
+        let arith = feeder.starts_with("$((");
         let mut text = feeder.consume(1);
 
-        if let Some(pc) = ParenCommand::parse(feeder, core, true)? {
+        let pc = ParenCommand::parse(feeder, core, true).map_err(|e| match (arith, e) {
+            (true, ParseError::UnexpectedEof(_)) | (true, ParseError::MissingTerminator(..))
+                => ParseError::MissingTerminator("$((".to_string(), "))".to_string(), arith_pos),
+            (_, e) => e.in_construct("$(", ")", pos),
+        })?;
+        if let Some(pc) = pc {
+            feeder.mark(start, Token::Variable);
             text += &pc.get_text();
             Ok(Some(CommandSubstitution {text: text, command: pc} ))
         }else{
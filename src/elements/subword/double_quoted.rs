@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::feeder::Token;
 use crate::utils::exit;
 use crate::error::parse::ParseError;
 use crate::error::exec::ExecError;
@@ -136,11 +137,14 @@ impl DoubleQuoted {
         }
     }
 
-    fn eat_doller(feeder: &mut Feeder, ans: &mut Self) -> bool {
-        match feeder.starts_with("$") {
-            true  => Self::set_simple_subword(feeder, ans, 1),
-            false => false,
+    fn eat_doller(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
+        if ! feeder.starts_with("$") {
+            return false;
         }
+        let start = feeder.offset();
+        Self::set_simple_subword(feeder, ans, 1);
+        feeder.mark_dollar_name(start, core);
+        true
     }
 
     fn eat_escaped_char(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
@@ -176,24 +180,27 @@ impl DoubleQuoted {
             return Ok(None);
         }
         let mut ans = Self::default();
+        let start = feeder.offset();
+        let pos = feeder.pos(1);
         ans.text = feeder.consume(1);
 
         loop {
             while Self::eat_braced_param(feeder, &mut ans, core)?
                || Self::eat_command_substitution(feeder, &mut ans, core)?
                || Self::eat_special_or_positional_param(feeder, &mut ans, core)
-               || Self::eat_doller(feeder, &mut ans)
+               || Self::eat_doller(feeder, &mut ans, core)
                || Self::eat_escaped_char(feeder, &mut ans, core)
                || Self::eat_name(feeder, &mut ans, core)
                || Self::eat_other(feeder, &mut ans, core) {}
 
             if feeder.starts_with("\"") {
                 ans.text += &feeder.consume(1);
+                feeder.mark(start, Token::Str);
                 return Ok(Some(ans));
             }else if feeder.len() > 0 {
                 exit::internal("unknown chars in double quoted word");
             }else{
-                feeder.feed_additional_line(core)?;
+                feeder.feed_additional_line(core).map_err(|e| e.in_quote("\"", pos.clone()))?;
             }
         }
     }
@@ -1,7 +1,8 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{ShellCore, Feeder};
+use crate::{exit, ShellCore, Feeder};
+use crate::feeder::Token;
 use crate::error::exec::ExecError;
 use super::Subword;
 
@@ -18,6 +19,9 @@ impl Subword for Parameter {
         if ! self.text.starts_with("$") {
             return Ok(());
         }
+        if core.db.flags.contains('u') && core.db.is_unset(&self.text[1..]) {
+            return Err(exit::unbound_variable(&self.text[1..], core));
+        }
         let value = core.db.get_param(&self.text[1..]).unwrap_or(String::new());
         self.text = value.to_string();
         Ok(())
@@ -30,7 +34,7 @@ impl Parameter {
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
         match feeder.scanner_dollar_special_and_positional_param(core) {
             0 => None,
-            n => Some(Self { text: feeder.consume(n) } ),
+            n => Some(Self { text: feeder.consume_token(n, Token::Variable) } ),
         }
     }
 }
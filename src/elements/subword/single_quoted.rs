@@ -2,6 +2,8 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::feeder::Token;
+use crate::error::parse::ParseError;
 use super::Subword;
 
 #[derive(Debug, Clone)]
@@ -29,12 +31,12 @@ impl Subword for SingleQuoted {
 }
 
 impl SingleQuoted {
-    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Option<Self> {
-        match feeder.scanner_single_quoted_subword(core) {
-            0 => None,
+    pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
+        match feeder.scanner_single_quoted_subword(core)? {
+            0 => Ok(None),
             n => {
-                let s = feeder.consume(n);
-                Some(SingleQuoted{ text: s })
+                let s = feeder.consume_token(n, Token::Str);
+                Ok(Some(SingleQuoted{ text: s }))
             },
         }
     }
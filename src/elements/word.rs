@@ -9,7 +9,6 @@ mod split;
 
 use crate::{ShellCore, Feeder};
 use crate::elements::subword;
-use crate::error;
 use crate::error::parse::ParseError;
 use crate::error::exec::ExecError;
 use super::subword::Subword;
@@ -58,18 +57,18 @@ impl Word {
         let mut ws = vec![];
         for w in ws_after_brace_exp {
             let expanded = w.tilde_and_dollar_expansion(core)?;
-            ws.append( &mut expanded.split_and_path_expansion(core) );
+            ws.append( &mut expanded.split_and_path_expansion(core)? );
         }
 
         Ok( Self::make_args(&mut ws) )
     }
 
     pub fn eval_as_value(&self, core: &mut ShellCore) -> Option<String> {
-        let mut ws = match self.tilde_and_dollar_expansion(core) {
-            Ok(w) => w.split_and_path_expansion(core),
+        let mut ws = match self.tilde_and_dollar_expansion(core)
+                           .and_then(|w| w.split_and_path_expansion(core)) {
+            Ok(ws) => ws,
             Err(e)    => {
-                let msg = format!("{:?}", &e);
-                error::print(&msg, core);
+                e.print(core);
                 return None;
             },
         };
@@ -81,8 +80,7 @@ impl Word {
         match self.tilde_and_dollar_expansion(core) {
             Ok(mut w) => w.make_unquoted_word(),
             Err(e)    => {
-                let msg = format!("{:?}", &e);
-                error::print(&msg, core);
+                e.print(core);
                 return None;
             },
         }
@@ -92,8 +90,7 @@ impl Word {
         match self.tilde_and_dollar_expansion(core) {
             Ok(mut w) => Some(w.make_glob_string()),
             Err(e)    => {
-                let msg = format!("{:?}", &e);
-                error::print(&msg, core);
+                e.print(core);
                 return None;
             },
         }
@@ -106,13 +103,12 @@ impl Word {
         Ok(w)
     }
 
-    pub fn split_and_path_expansion(&self, core: &mut ShellCore) -> Vec<Word> {
+    pub fn split_and_path_expansion(&self, core: &mut ShellCore) -> Result<Vec<Word>, ExecError> {
         let mut ans = vec![];
-        let extglob = core.shopts.query("extglob");
         for mut w in split::eval(self, core) {
-            ans.append(&mut path_expansion::eval(&mut w, extglob) );
+            ans.append(&mut path_expansion::eval(&mut w, core)? );
         }
-        ans
+        Ok(ans)
     }
 
     fn make_args(words: &mut Vec<Word>) -> Vec<String> {
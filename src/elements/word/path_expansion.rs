@@ -1,37 +1,115 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use crate::ShellCore;
+use crate::core::options::Options;
+use crate::error::exec::ExecError;
 use crate::elements::subword::Subword;
 use crate::elements::word::Word;
-use crate::utils::directory;
+use crate::utils::{directory, glob};
 use super::subword::simple::SimpleSubword;
 
-pub fn eval(word: &mut Word, extglob: bool) -> Vec<Word> {
-    let paths = expand(&word.make_glob_string(), extglob);
+pub fn eval(word: &mut Word, core: &mut ShellCore) -> Result<Vec<Word>, ExecError> {
+    let pattern = word.make_glob_string();
+    if core.db.flags.contains('f') || ! is_pattern(&pattern) {
+        return Ok(vec![word.clone()]);
+    }
+
+    let globignore = core.db.get_param("GLOBIGNORE").unwrap_or_default();
+    let paths = match globignore.is_empty() {
+        true  => expand(&pattern, &core.shopts),
+        false => {
+            let mut shopts = core.shopts.clone();
+            shopts.set("dotglob", true);
+            let mut paths = expand(&pattern, &shopts);
+            ignore(&mut paths, &globignore, &shopts);
+            paths
+        },
+    };
+
     if paths.is_empty() {
-        return vec![word.clone()];
+        if core.shopts.query("failglob") {
+            return Err(ExecError::NoMatch(word.text.clone()));
+        }
+        if ! core.shopts.query("nullglob") {
+            return Ok(vec![word.clone()]);
+        }
     }
 
     let subwd = |path| Box::new(SimpleSubword{ text: path });
     let wd = |path| Word::from( subwd(path) as Box::<dyn Subword>);
-    paths.iter().map(|p| wd(p.to_string())).collect()
+    Ok(paths.iter().map(|p| wd(p.to_string())).collect())
 }
 
-fn expand(pattern: &str, extglob: bool) -> Vec<String> {
-    if "*?@+![".chars().all(|c| ! pattern.contains(c)) {
-        return vec![];
+/* whether the glob string has an unescaped wildcard */
+fn is_pattern(s: &str) -> bool {
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => { chars.next(); },
+            '*' | '?' => return true,
+            '[' if chars.clone().any(|c| c == ']') => return true,
+            '@' | '+' | '!' if chars.peek() == Some(&'(') => return true,
+            _ => {},
+        }
     }
-        
+    false
+}
+
+/* removes the paths matching a pattern in GLOBIGNORE */
+fn ignore(paths: &mut Vec<String>, globignore: &str, shopts: &Options) {
+    let extglob = shopts.query("extglob");
+    let patterns: Vec<&str> = globignore.split(':').filter(|p| ! p.is_empty()).collect();
+    paths.retain(|p| ! patterns.iter().any(|pat| glob::parse_and_compare(p, pat, extglob)));
+}
+
+fn expand(pattern: &str, shopts: &Options) -> Vec<String> {
+    let globstar = shopts.query("globstar");
+    let dotglob = shopts.query("dotglob");
     let mut paths = vec!["".to_string()];
+    let dir_globs: Vec<&str> = pattern.split("/").collect();
+
+    for (i, dir_glob) in dir_globs.iter().enumerate() {
+        if globstar && *dir_glob == "**" {
+            paths = paths.iter()
+                    .map(|c| globstar_paths(c, &dir_globs[i+1..], dotglob))
+                    .collect::<Vec<Vec<String>>>()
+                    .concat();
+            continue;
+        }
 
-    for dir_glob in pattern.split("/") {
         paths = paths.iter()
-                .map(|c| directory::glob(&c, &dir_glob, extglob) )
+                .map(|c| directory::glob(c, dir_glob, shopts) )
                 .collect::<Vec<Vec<String>>>()
                 .concat();
     }
 
     paths.iter_mut().for_each(|e| {e.pop();} );
+    paths.retain(|p| ! p.is_empty());
     paths.sort();
+    paths.dedup();
+    paths
+}
+
+/* ** matches the directory itself and the directories under it, and also
+ * all the files at the end of the pattern. Symbolic links to directories
+ * are listed but not searched, nor used for the following parts. */
+fn globstar_paths(dir: &str, rest: &[&str], dotglob: bool) -> Vec<String> {
+    if ! dir.is_empty() && ! directory::is_dir(dir) {
+        return vec![];
+    }
+
+    let mut paths = directory::descendants(dir, dotglob);
+    match rest {
+        []   => paths.insert(0, dir.to_string() + "/"),
+        [""] => {
+            paths.retain(|p| directory::is_dir(p));
+            paths.insert(0, dir.to_string());
+        },
+        _    => {
+            paths.retain(|p| directory::is_real_dir(p));
+            paths.insert(0, dir.to_string());
+        },
+    }
     paths
 }
@@ -15,6 +15,11 @@ pub enum ExecError {
     InvalidBase(String),
     InvalidName(String),
     InvalidOption(String),
+    NoMatch(String),
+    HistoryBadWordSpecifier(String),
+    HistoryEventNotFound(String),
+    HistorySubstitutionFailed(String),
+    HistoryUnrecognizedModifier(String),
     Interrupted,
     ValidOnlyInFunction(String),
     VariableReadOnly(String),
@@ -23,6 +28,7 @@ pub enum ExecError {
     ParseError(ParseError),
     Recursion(String),
     SubstringMinus(i64),
+    UnboundVariable(String),
     Other(String),
 }
 
@@ -43,6 +49,11 @@ impl From<&ExecError> for String {
             ExecError::InvalidName(name) => format!("`{}': invalid name", name),
             ExecError::InvalidBase(b) => format!("{0}: invalid arithmetic base (error token is \"{0}\")", b),
             ExecError::InvalidOption(opt) => format!("{}: invalid option", opt),
+            ExecError::NoMatch(pattern) => format!("no match: {}", pattern),
+            ExecError::HistoryBadWordSpecifier(s) => format!("{}: bad word specifier", s),
+            ExecError::HistoryEventNotFound(s) => format!("{}: event not found", s),
+            ExecError::HistorySubstitutionFailed(s) => format!("{}: substitution failed", s),
+            ExecError::HistoryUnrecognizedModifier(s) => format!("{}: unrecognized history modifier", s),
             ExecError::Interrupted => "interrupted".to_string(),
             ExecError::AssignmentToNonVariable(right) => format!("attempted assignment to non-variable (error token is \"{}\")", right),
             ExecError::ValidOnlyInFunction(com) => format!("{}: can only be used in a function", &com),
@@ -52,6 +63,7 @@ impl From<&ExecError> for String {
             ExecError::ParseError(p) => From::from(p),
             ExecError::Recursion(token) => format!("{0}: expression recursion level exceeded (error token is \"{0}\")", token), 
             ExecError::SubstringMinus(n) => format!("{}: substring expression < 0", n),
+            ExecError::UnboundVariable(name) => format!("{}: unbound variable", name),
             ExecError::Other(name) => name.to_string(),
         }
     }
@@ -2,32 +2,107 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
-use super::input::InputError;
+
+/* where a parse error occurs; column is 1-origin and counted in chars */
+#[derive(Debug, Clone, Default)]
+pub struct SourcePos {
+    pub file: String,
+    pub lineno: usize,
+    pub column: usize,
+    pub len: usize,
+    pub line: String,
+}
+
+impl SourcePos {
+    fn print_line(&self) {
+        if self.lineno == 0 || self.line.is_empty() {
+            return;
+        }
+
+        let indent: String = self.line.chars().take(self.column.saturating_sub(1))
+                             .map(|c| if c == '\t' {'\t'} else {' '}).collect();
+        eprintln!("{}", &self.line);
+        eprintln!("{}{}", &indent, "^".repeat(std::cmp::max(self.len, 1)));
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum ParseError {
-    UnexpectedSymbol(String),
-    Input(InputError),
+    UnexpectedSymbol(String, SourcePos),
+    UnterminatedQuote(String, SourcePos),
+    MissingTerminator(String, String, SourcePos), //opener, terminator, where the opener is
+    UnexpectedEof(SourcePos),
+    BadRedirect(String, SourcePos),
+    Interrupted,
+}
+
+/* a token in messages; a newline is shown by its name as bash does */
+fn token(s: &str) -> &str {
+    match s.trim_end_matches('\n') {
+        "" => "newline",
+        t  => t,
+    }
 }
 
 impl From<&ParseError> for String {
     fn from(e: &ParseError) -> String {
         match e {
-            ParseError::UnexpectedSymbol(s) => format!("Unexpected token: {}", s),
-            ParseError::Input(e) => From::from(e),
+            ParseError::UnexpectedSymbol(s, _) | ParseError::BadRedirect(s, _)
+                => format!("syntax error near unexpected token `{}'", token(s)),
+            ParseError::UnterminatedQuote(q, _)
+                => format!("unexpected EOF while looking for matching `{}'", q),
+            ParseError::MissingTerminator(open, term, _)
+                => format!("unexpected EOF while looking for `{}' to close `{}'", term, open),
+            ParseError::UnexpectedEof(_) => "syntax error: unexpected end of file".to_string(),
+            ParseError::Interrupted => "interrupted".to_string(),
         }
     }
 }
 
 impl ParseError {
+    pub fn pos(&self) -> Option<&SourcePos> {
+        match self {
+            ParseError::UnexpectedSymbol(_, pos)
+            | ParseError::UnterminatedQuote(_, pos)
+            | ParseError::MissingTerminator(_, _, pos)
+            | ParseError::UnexpectedEof(pos)
+            | ParseError::BadRedirect(_, pos) => Some(pos),
+            ParseError::Interrupted => None,
+        }
+    }
+
+    /* an EOF inside a quote opened at pos */
+    pub fn in_quote(self, quote: &str, pos: SourcePos) -> ParseError {
+        match self {
+            ParseError::UnexpectedEof(_) => ParseError::UnterminatedQuote(quote.to_string(), pos),
+            e => e,
+        }
+    }
+
+    /* an EOF inside a construct opened at pos; inner constructs have been reported already */
+    pub fn in_construct(self, opener: &str, terminator: &str, pos: SourcePos) -> ParseError {
+        match self {
+            ParseError::UnexpectedEof(_)
+                => ParseError::MissingTerminator(opener.to_string(), terminator.to_string(), pos),
+            e => e,
+        }
+    }
+
     pub fn print(&self, core: &mut ShellCore) {
         let name = core.db.get_param("0").unwrap();
         let s: String = From::<&ParseError>::from(self);
         if core.db.flags.contains('i') {
             eprintln!("{}: {}", &name, &s);
-        }else{
-            let lineno = core.db.get_param("LINENO").unwrap_or("".to_string());
-            eprintln!("{}: line {}: {}", &name, &lineno, s);
+            return;
         }
+
+        let default_pos = SourcePos::default();
+        let pos = self.pos().unwrap_or(&default_pos);
+        let (file, lineno) = match pos.lineno {
+            0 => (name, core.db.get_param("LINENO").unwrap_or("".to_string())),
+            n => (if pos.file.is_empty() {name} else {pos.file.clone()}, n.to_string()),
+        };
+        eprintln!("{}: line {}: {}", &file, &lineno, s);
+        pos.print_line();
     }
 }
@@ -8,10 +8,22 @@ use std::{io, process};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Lines};
 use crate::ShellCore;
+use crate::core::history::expansion::HistoryExpansion;
 use crate::error::input::InputError;
-use crate::error::parse::ParseError;
+use crate::error::parse::{ParseError, SourcePos};
 use std::sync::atomic::Ordering::Relaxed;
 
+/* the kinds of tokens the parser records for syntax highlighting */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Keyword,
+    Command,
+    Str,
+    Variable,
+    Comment,
+    Redirect,
+}
+
 #[derive(Debug, Default)]
 pub struct Feeder {
     remaining: String,
@@ -19,6 +31,11 @@ pub struct Feeder {
     pub nest: Vec<(String, Vec<String>)>,
     pub lineno: usize,
     script_lines: Option<Lines<BufReader<File>>>,
+    source: String,
+    lines: Vec<String>, //the lines from first_lineno for error messages
+    first_lineno: usize,
+    pub tokens: Option<Vec<(usize, usize, Token)>>, //byte ranges in the given text
+    token_base: usize,
 }
 
 impl Feeder {
@@ -27,13 +44,67 @@ impl Feeder {
             remaining: s.to_string(),
             nest: vec![("".to_string(), vec![])],
             lineno: 1,
+            lines: s.lines().map(|ln| ln.to_string()).collect(),
+            first_lineno: 1,
             ..Default::default()
         }
     }
 
+    /* lets the parser record tokens without reading additional lines;
+     * the parse result is used only for syntax highlighting */
+    pub fn record_tokens(&mut self) {
+        self.tokens = Some(vec![]);
+        self.token_base = self.remaining.len();
+    }
+
+    pub fn offset(&self) -> usize {
+        self.token_base.saturating_sub(self.remaining.len())
+    }
+
+    /* records the text from the offset from to the current one as a token */
+    pub fn mark(&mut self, from: usize, token: Token) {
+        let to = self.offset();
+        if let Some(tokens) = self.tokens.as_mut() {
+            if from < to {
+                tokens.push((from, to, token));
+            }
+        }
+    }
+
+    /* records a "$" consumed at from and the name following it as a variable */
+    pub fn mark_dollar_name(&mut self, from: usize, core: &mut ShellCore) {
+        let len = self.scanner_name(core);
+        let to = self.offset() + len;
+        if let Some(tokens) = self.tokens.as_mut() {
+            tokens.push((from, to, Token::Variable));
+        }
+    }
+
+    pub fn consume_token(&mut self, cutpos: usize, token: Token) -> String {
+        let from = self.offset();
+        let ans = self.consume(cutpos);
+        self.mark(from, token);
+        ans
+    }
+
     pub fn set_file(&mut self, s: &str) {
         let file = File::open(s).unwrap();
         self.script_lines = Some(BufReader::new(file).lines());
+        self.source = s.to_string();
+    }
+
+    /* the position of the head of the remaining text */
+    pub fn pos(&self, len: usize) -> SourcePos {
+        let line = self.lineno.checked_sub(self.first_lineno)
+                       .and_then(|i| self.lines.get(i))
+                       .cloned().unwrap_or_default();
+        let rest = self.remaining.split('\n').next().unwrap_or("");
+        let column = match line.len().checked_sub(rest.len()) {
+            Some(n) if line.is_char_boundary(n) && line[n..] == *rest => line[..n].chars().count() + 1,
+            _ => 1,
+        };
+
+        SourcePos { file: self.source.clone(), lineno: self.lineno, column, len, line }
     }
 
     pub fn consume(&mut self, cutpos: usize) -> String {
@@ -68,7 +139,16 @@ impl Feeder {
     }
 
     pub fn rewind(&mut self) {
-        self.remaining = self.backup.pop().expect("SUSHI INTERNAL ERROR (backup error)");
+        let backup = self.backup.pop().expect("SUSHI INTERNAL ERROR (backup error)");
+        let consumed = backup.len().saturating_sub(self.remaining.len());
+        if let Some(s) = backup.get(..consumed) {
+            self.lineno -= s.matches('\n').count();
+        }
+        self.remaining = backup;
+        let offset = self.offset();
+        if let Some(tokens) = self.tokens.as_mut() {
+            tokens.retain(|t| t.0 < offset);
+        }
     }   
 
     fn read_script(&mut self, core: &mut ShellCore) -> Result<String, InputError> {
@@ -94,13 +174,52 @@ impl Feeder {
         }
     }
 
+    fn read_terminal(&mut self, core: &mut ShellCore, prompt: &str) -> Result<String, InputError> {
+        let mut preset = String::new();
+        loop {
+            let line = terminal::read_line(core, prompt, &preset)?;
+            preset.clear();
+            if ! core.db.flags.contains('H') {
+                return Ok(line);
+            }
+
+            match core.expand_history(&line) {
+                Ok(HistoryExpansion::Unchanged) => return Ok(line),
+                Ok(HistoryExpansion::Expanded(s)) => {
+                    if core.shopts.query("histverify") {
+                        core.delete_history(0);
+                        preset = s.trim_end_matches('\n').to_string();
+                        continue;
+                    }
+                    eprintln!("{}", s.trim_end_matches('\n'));
+                    core.history[0] = s.trim_end_matches('\n').to_string();
+                    return Ok(s);
+                },
+                Ok(HistoryExpansion::PrintOnly(s)) => {
+                    eprintln!("{}", s.trim_end_matches('\n'));
+                    core.history[0] = s.trim_end_matches('\n').to_string();
+                },
+                Err(e) => {
+                    e.print(core);
+                    core.delete_history(0);
+                    if core.shopts.query("histreedit") {
+                        preset = line.trim_end_matches('\n').to_string();
+                    }
+                },
+            }
+        }
+    }
+
     fn feed_additional_line_core(&mut self, core: &mut ShellCore) -> Result<(), InputError> {
+        if self.tokens.is_some() {
+            return Err(InputError::Eof);
+        }
         if core.sigint.load(Relaxed) {
             return Err(InputError::Interrupt);
         }
 
         let line = match ! core.read_stdin && self.script_lines.is_none() {
-            true  => terminal::read_line(core, "PS2"),
+            true  => self.read_terminal(core, "PS2"),
             false => self.read_script(core),
         };
 
@@ -115,18 +234,21 @@ impl Feeder {
             Ok(()) => Ok(()),
             Err(InputError::Eof) => {
                 core.db.exit_status = 2;
-                return Err(ParseError::Input(InputError::Eof));
+                return Err(ParseError::UnexpectedEof(self.pos(0)));
             },
             Err(InputError::Interrupt) => {
                 core.db.exit_status = 130;
-                Err(ParseError::Input(InputError::Interrupt))
+                Err(ParseError::Interrupted)
             },
         }
     }
 
     pub fn feed_line(&mut self, core: &mut ShellCore) -> Result<(), InputError> {
         let line = match ! core.read_stdin && self.script_lines.is_none() {
-            true  => terminal::read_line(core, "PS1"),
+            true  => {
+                core.run_prompt_command();
+                self.read_terminal(core, "PS1")
+            },
             false => self.read_script(core),
         };
 
@@ -138,6 +260,12 @@ impl Feeder {
             eprint!("{}", &line);
         }
 
+        if self.remaining.is_empty() {
+            self.lines.clear();
+            self.first_lineno = self.lineno;
+        }
+        self.lines.push(line.trim_end_matches('\n').to_string());
+
         match self.remaining.len() {
             0 => self.remaining = line,
             _ => self.remaining += &line,
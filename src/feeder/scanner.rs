@@ -3,6 +3,7 @@
 
 use super::Feeder;
 use crate::ShellCore;
+use crate::error::parse::ParseError;
 
 impl Feeder {
     fn feed_and_connect(&mut self, core: &mut ShellCore) {
@@ -113,13 +114,6 @@ impl Feeder {
         }
     }
 
-    pub fn scanner_history_expansion(&mut self, _: &mut ShellCore) -> usize {
-        match self.starts_with("!$") {
-            true  => 2,
-            false => 0,
-        }
-    }
-
     pub fn scanner_dollar_special_and_positional_param(&mut self, core: &mut ShellCore) -> usize {
         if ! self.starts_with("$") {
             return 0;
@@ -168,17 +162,17 @@ impl Feeder {
         self.scanner_chars(judge, core, 0)
     }
 
-    pub fn scanner_single_quoted_subword(&mut self, core: &mut ShellCore) -> usize {
+    pub fn scanner_single_quoted_subword(&mut self, core: &mut ShellCore) -> Result<usize, ParseError> {
         if ! self.starts_with("'") {
-            return 0;
+            return Ok(0);
         }
 
+        let pos = self.pos(1);
         loop {
             if let Some(n) = self.remaining[1..].find("'") {
-                return n + 2;
-            }else if ! self.feed_additional_line(core).is_ok() {
-                return 0;
+                return Ok(n + 2);
             }
+            self.feed_additional_line(core).map_err(|e| e.in_quote("'", pos.clone()))?;
         }
     }
 
@@ -278,7 +272,7 @@ impl Feeder {
 
     pub fn scanner_redirect_symbol(&mut self, core: &mut ShellCore) -> usize {
         self.backslash_check_and_feed(vec!["<<", ">", "&", "<"], core);
-        self.scanner_one_of(&["<<<", "&>", ">&", ">>", "<", ">"])
+        self.scanner_one_of(&["<<<", "&>", ">&", ">>", ">|", "<", ">"])
     }
 
     pub fn scanner_parameter_alternative_symbol(&mut self) -> usize {
@@ -2,21 +2,31 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod completion;
-
-use crate::{file_check, ShellCore};
-use crate::utils::file;
+mod edit;
+mod highlight;
+mod menu;
+mod search;
+mod suggest;
+mod vi;
+
+use crate::ShellCore;
+use crate::core::compspec::CompSpec;
+use crate::core::keymap::{Binding, Lookup};
+use crate::core::prompt::{NONPRINT_BEGIN, NONPRINT_END};
+use self::edit::EditResult;
+use self::highlight::Style;
+use self::menu::Menu;
+use self::vi::ViState;
 use crate::error::input::InputError;
+use std::collections::{HashMap, VecDeque};
 use std::io;
-use std::fs::File;
+use std::io::Stdin;
 use std::io::{Write, Stdout};
 use std::sync::atomic::Ordering::Relaxed;
-use std::path::Path;
-use nix::unistd;
-use nix::unistd::User;
 use termion::cursor::DetectCursorPos;
 use termion::event;
 use termion::raw::{IntoRawMode, RawTerminal};
-use termion::input::TermRead;
+use termion::input::{Keys, TermRead};
 use unicode_width::UnicodeWidthChar;
 
 struct Terminal {
@@ -28,62 +38,27 @@ struct Terminal {
     hist_ptr: usize,
     prompt_width_map: Vec<usize>,
     /* for extended completion */
-    completion_candidate: String,
-    tab_row: i32,
-    tab_col: i32,
+    tab_num: usize,
+    menu: Option<Menu>,
     escape_at_completion: bool,
-}
-
-fn oct_string(s: &str) -> bool {
-    if s.chars().nth(0) != Some('\\') {
-        return false;
-    }
-
-    for i in 1..4 {
-        match s.chars().nth(i) {
-            Some(c) => {
-                if c < '0' || '9' < c {
-                    return false;
-                }
-            },
-            _ => return false,
-        }
-    }
-
-    true
-}
-
-fn oct_to_hex_in_str(from: &str) -> String {
-    let mut i = 0;
-    let mut pos = vec![];
-
-    for ch in from.chars() {
-        if oct_string(&from[i..]) {
-            pos.push(i);
-        }
-        i += ch.len_utf8();
-    }
-
-    let mut prev = 0;
-    let mut ans = String::new();
-    for p in pos {
-        ans += &from[prev..p];
-        if let Ok(n) = u32::from_str_radix(&from[p+1..p+4], 8) {
-            ans += &char::from_u32(n).unwrap().to_string();
-        }
-        prev = p+4;
-    }
-    ans += &from[prev..];
-    ans
+    completion_word: (usize, usize),
+    compspec: Option<CompSpec>,
+    bell: bool,
+    /* for keymaps and vi mode */
+    pending: VecDeque<event::Key>,
+    undo_list: Vec<(Vec<char>, usize)>,
+    vi: ViState,
+    /* for syntax highlighting */
+    highlighted: Vec<char>,
+    command_styles: HashMap<String, Style>,
+    /* for autosuggestions */
+    suggestion: String,
 }
 
 impl Terminal {
     pub fn new(core: &mut ShellCore, ps: &str) -> Self {
-        let raw_prompt = core.db.get_param(ps).unwrap_or(String::new());
-        let ansi_on_prompt = oct_to_hex_in_str(&raw_prompt);
-
-        let replaced_prompt = Self::make_prompt_string(&ansi_on_prompt);
-        let prompt = replaced_prompt.replace("\\[", "").replace("\\]", "").to_string();
+        let expanded_prompt = core.expand_prompt(ps);
+        let prompt = expanded_prompt.replace([NONPRINT_BEGIN, NONPRINT_END], "");
         print!("{}", prompt);
         io::stdout().flush().unwrap();
 
@@ -97,74 +72,28 @@ impl Terminal {
             chars: prompt.chars().collect(),
             head: prompt.chars().count(),
             hist_ptr: 0,
-            prompt_width_map: Self::make_width_map(&replaced_prompt),
-            completion_candidate: String::new(),
-            tab_row: -1,
-            tab_col: -1,
+            prompt_width_map: Self::make_width_map(&expanded_prompt),
+            tab_num: 0,
+            menu: None,
             escape_at_completion: true,
+            completion_word: (0, 0),
+            compspec: None,
+            bell: core.keymap.variable("bell-style") != "none",
+            pending: VecDeque::new(),
+            undo_list: vec![],
+            vi: ViState::default(),
+            highlighted: vec![],
+            command_styles: HashMap::new(),
+            suggestion: String::new(),
         }
     }
 
-    fn get_branch(cwd: &String) -> String {
-        let mut dirs: Vec<String> = cwd.split("/").map(|s| s.to_string()).collect();
-        while dirs.len() > 0 {
-            let path = dirs.join("/") + "/.git/HEAD";
-            dirs.pop();
-
-            if ! file_check::is_regular_file(&path) {
-                continue;
-            }
-
-            if let Ok(mut f) = File::open(Path::new(&path)){
-                return match f.read_line() {
-                    Ok(Some(s)) => s.replace("ref: refs/heads/","") + "🌵",
-                    _ => "".to_string(),
-                };
-            }
-        }
-
-        "".to_string()
-    }
-
-    fn make_prompt_string(raw: &str) -> String {
-        let uid = unistd::getuid();
-        let user = match User::from_uid(uid) {
-            Ok(Some(u)) => u.name,
-            _ => "".to_string(),
-        };
-        let hostname = match unistd::gethostname() {
-            Ok(h) => file::oss_to_name(&h),
-            _ => "".to_string(),
-        };
-
-        let homedir = match User::from_uid(uid) {
-            Ok(Some(u)) => file::buf_to_name(&u.dir),
-            _ => "".to_string(),
-        };
-        let mut cwd = match unistd::getcwd() {
-            Ok(p) => file::buf_to_name(&p),
-            _ => "".to_string(),
-        };
-        let branch = Self::get_branch(&cwd);
-
-        if cwd.starts_with(&homedir) {
-            cwd = cwd.replacen(&homedir, "~", 1);
-        }
-
-        raw.replace("\\u", &user)
-           .replace("\\h", &hostname)
-           .replace("\\w", &cwd)
-           .replace("\\b", &branch)
-           .to_string()
-    }
-
     fn make_width_map(prompt: &str) -> Vec<usize> {
-        let tmp = prompt.replace("\\[", "\x01").replace("\\]", "\x02").to_string();
         let mut in_escape = false;
         let mut ans = vec![];
-        for c in tmp.chars() {
-            if c == '\x01' || c == '\x02' {
-                in_escape = c == '\x01';
+        for c in prompt.chars() {
+            if c == NONPRINT_BEGIN || c == NONPRINT_END {
+                in_escape = c == NONPRINT_BEGIN;
                 continue;
             }
 
@@ -237,6 +166,7 @@ impl Terminal {
     }
 
     fn rewrite(&mut self, erase: bool) {
+        self.highlighted.clear();
         self.goto(0);
         if erase {
             self.write(&termion::clear::AfterCursor.to_string());
@@ -333,119 +263,175 @@ impl Terminal {
         self.rewrite(true);
     }
 
-    pub fn set_double_tab_completion(&mut self) {
-        let s = self.completion_candidate.clone() + " ";
-        self.replace_input(&s);
+    pub fn next_key(&mut self, keys: &mut Keys<Stdin>) -> Option<event::Key> {
+        let key = match self.pending.pop_front() {
+            Some(k) => k,
+            None => keys.next()?.ok()?,
+        };
+        self.vi.log.push(key);
+        Some(key)
+    }
+
+    /* keeps a snapshot for undo and the keys of the last change for the vi . command */
+    fn record_change(&mut self, before: (Vec<char>, usize), function: &str,
+                     vi_mode: bool, command_mode: bool) {
+        if function == "undo" || function == "vi-undo" {
+            return;
+        }
+
+        let changed = before.0 != self.chars;
+        let entered_insertion = command_mode && ! self.vi.command_mode;
+        if (changed && (! vi_mode || command_mode)) || entered_insertion {
+            self.undo_list.push(before);
+        }
+        if command_mode && changed && function != "vi-redo" && ! self.vi.inserting {
+            self.vi.last_change = self.vi.log.clone();
+        }
+
+        let min = self.prompt.chars().count();
+        if vi_mode && self.vi.command_mode && self.head == self.chars.len() && self.head > min {
+            self.head -= 1;
+            self.goto(self.head);
+            self.flush();
+        }
     }
 
     pub fn cloop(&mut self) {
-        print!("\x07");
-        self.flush();
+        if self.bell {
+            print!("\x07");
+            self.flush();
+        }
     }
 }
 
-fn is_completion_key(key: event::Key) -> bool {
-    match key {
-        event::Key::Char('\t') 
-            | event::Key::Left | event::Key::Down
-            | event::Key::Right | event::Key::Up => true,
-        _ => false,
-    }
+fn is_arrow_key(key: &event::Key) -> bool {
+    matches!(key, event::Key::Left | event::Key::Down | event::Key::Right | event::Key::Up)
 }
 
-fn on_arrow_key(term: &mut Terminal, core: &mut ShellCore, key: &event::Key, tab_num: usize) {
-    if tab_num > 1 {
-        match key {
-            event::Key::Down  => term.tab_row += 1,
-            event::Key::Up    => term.tab_row -= 1,
-            event::Key::Right => term.tab_col += 1,
-            event::Key::Left  => term.tab_col -= 1,
-            _ => {},
-        }
-        term.completion(core, tab_num);
-    }else{
-        match key {
-            event::Key::Down  => term.call_history(-1, core),
-            event::Key::Up    => term.call_history(1, core),
-            event::Key::Right => term.shift_cursor(1),
-            event::Key::Left  => term.shift_cursor(-1),
-            _ => {},
-        }
-    }
+/* functions which keep the completion menu */
+fn on_menu(function: &str) -> bool {
+    matches!(function, "complete" | "menu-complete" | "menu-complete-backward"
+             | "possible-completions" | "accept-line" | "abort")
 }
 
-pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputError>{
+pub fn read_line(core: &mut ShellCore, prompt: &str, preset: &str) -> Result<String, InputError>{
     let mut term = Terminal::new(core, prompt);
+    if ! preset.is_empty() {
+        term.chars.extend(preset.chars());
+        term.head = term.chars.len();
+        term.rewrite(false);
+        term.highlight(core);
+    }
     let mut term_size = Terminal::size();
-    core.history.insert(0, String::new());
-    let mut prev_key = event::Key::Char('a');
-    let mut tab_num = 0;
+    core.insert_history(0, String::new());
+    let mut keys = io::stdin().keys();
+    let mut seq = String::new();
+    let mut last_function = String::new();
+
+    loop {
+        let vi_mode = core.keymap.variable("editing-mode") == "vi";
+        let command_mode = vi_mode && term.vi.command_mode;
+        if command_mode && term.vi.count.is_none() && seq.is_empty() {
+            term.vi.log.clear();
+        }
 
-    for c in io::stdin().keys() {
+        let key = match term.next_key(&mut keys) {
+            Some(event::Key::Alt(c)) if vi_mode => {
+                term.pending.push_front(event::Key::Char(c));
+                event::Key::Esc
+            },
+            Some(k) => k,
+            None => break,
+        };
         term.check_size_change(&mut term_size);
 
-        match c.as_ref().unwrap() {
-            event::Key::Ctrl('a') => term.goto_origin(),
-            event::Key::Ctrl('b') => term.shift_cursor(-1),
-            event::Key::Ctrl('c') => {
-                core.sigint.store(true, Relaxed);
-                term.goto(term.chars.len());
-                term.write("^C\r\n");
-                return Err(InputError::Interrupt);
-            },
-            event::Key::Ctrl('d') => {
-                if term.chars.len() == term.prompt.chars().count() {
-                    term.write("\r\n");
-                    return Err(InputError::Eof);
-                }else{
-                    term.delete();
-                }
+        if key == event::Key::Ctrl('c') {
+            core.sigint.store(true, Relaxed);
+            term.erase_suggestion();
+            term.close_menu();
+            term.goto(term.chars.len());
+            term.write("^C\r\n");
+            return Err(InputError::Interrupt);
+        }
+        if term.menu.is_some() && is_arrow_key(&key) {
+            term.menu_arrow(&key);
+            continue;
+        }
+
+        seq += &edit::keyseq(&key);
+        let map = match (vi_mode, command_mode) {
+            (false, _)    => "emacs",
+            (true, false) => "vi-insert",
+            (true, true)  => "vi-command",
+        };
+        let binding = match core.keymap.lookup(map, &seq) {
+            Lookup::Prefix => continue,
+            Lookup::Found(_) if command_mode && seq == "0" && term.vi.count.is_some() => {
+                Binding::Function("vi-arg-digit".to_string())
             },
-            event::Key::Ctrl('e') => term.goto_end(),
-            event::Key::Ctrl('f') => term.shift_cursor(1),
-            event::Key::Down |
-            event::Key::Left |
-            event::Key::Right |
-            event::Key::Up => on_arrow_key(&mut term, core, c.as_ref().unwrap(), tab_num),
-            event::Key::Backspace => term.backspace(),
-            event::Key::Delete => term.delete(),
-            event::Key::Char('\n') => {
-                if term.completion_candidate.len() > 0 {
-                    term.set_double_tab_completion();
-                }else{
-                    term.goto(term.chars.len());
-                    term.write("\r\n");
-                    term.chars.push('\n');
-                    break;
+            Lookup::Found(b) => b,
+            Lookup::NotFound => {
+                let mut chars = seq.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if ! c.is_control() && ! command_mode => {
+                        Binding::Function("self-insert".to_string())
+                    },
+                    _ => Binding::Function("abort".to_string()),
                 }
             },
-            event::Key::Char('\t') => {
-                if tab_num == 0 || prev_key == event::Key::Char('\t') {
-                    tab_num += 1;
-                }
-                if tab_num == 2 {
-                    term.tab_row = -1;
-                    term.tab_col = 0;
-                }else if tab_num > 2 {
-                    term.tab_row += 1;
+        };
+        let typed = std::mem::take(&mut seq);
+
+        let function = match binding {
+            Binding::Function(f) => f,
+            Binding::Macro(m) => {
+                for k in edit::macro_keys(&m).into_iter().rev() {
+                    term.pending.push_front(k);
                 }
-                term.completion(core, tab_num);
+                continue;
             },
-            event::Key::Char(c) => {
-                term.insert(*c);
+            Binding::Command(c) => {
+                term.execute_command(core, &c);
+                String::new()
             },
-            _  => {},
+        };
+
+        if ! on_menu(&function) {
+            term.close_menu();
         }
+        let before = (term.chars.clone(), term.head);
+        let repeat = match command_mode && ! function.starts_with("vi-") {
+            true  => term.vi.count.take().unwrap_or(1),
+            false => 1,
+        };
+        for _ in 0..repeat {
+            let last = (term.chars.clone(), term.head);
+            match term.call_function(core, &function, &typed, &last_function, &mut keys) {
+                EditResult::Continue => {},
+                EditResult::Accept => return Ok(finish_line(core, &term)),
+                EditResult::Eof => return Err(InputError::Eof),
+            }
+            if (&term.chars, term.head) == (&last.0, last.1) { //no more effect on repetition
+                break;
+            }
+        }
+        term.record_change(before, &function, vi_mode, command_mode);
+
         term.check_scroll();
-        prev_key = c.as_ref().unwrap().clone();
-        if ! is_completion_key(prev_key) {
-            tab_num = 0;
-            term.completion_candidate = String::new();
+        term.highlight(core);
+        term.suggest(core);
+        if function != "complete" {
+            term.tab_num = 0;
         }
+        last_function = function;
     }
 
+    Ok(finish_line(core, &term))
+}
+
+fn finish_line(core: &mut ShellCore, term: &Terminal) -> String {
     core.history[0] = term.get_string(term.prompt.chars().count());
     core.history[0].pop();
 
-    Ok(term.get_string(term.prompt.chars().count()))
+    term.get_string(term.prompt.chars().count())
 }
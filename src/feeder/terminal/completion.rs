@@ -3,17 +3,11 @@
 
 use crate::{file_check, Feeder, ShellCore, utils};
 use crate::core::builtins::completion;
+use crate::core::compspec;
+use crate::core::compspec::CompSpec;
 use crate::error::exec::ExecError;
 use crate::elements::command::simple::SimpleCommand;
-use crate::elements::command::Command;
-use crate::elements::io::pipe::Pipe;
 use crate::feeder::terminal::Terminal;
-use termion::cursor::DetectCursorPos;
-use unicode_width::UnicodeWidthStr;
-
-fn str_width(s: &str) -> usize {
-    UnicodeWidthStr::width(s)
-}
 
 fn common_length(chars: &Vec<char>, s: &String) -> usize {
     let max_len = chars.len();
@@ -41,6 +35,22 @@ fn common_string(paths: &Vec<String>) -> String {
     ref_chars[..common_len].iter().collect()
 }
 
+/* turns the last path component into a glob matching it in any case */
+fn ignore_case_pattern(path: &str) -> String {
+    let (dir, key) = match path.rfind('/') {
+        Some(p) => path.split_at(p+1),
+        None    => ("", path),
+    };
+
+    let key: String = key.chars().map(|c| {
+        match c.is_alphabetic() {
+            true  => format!("[{}{}]", c.to_lowercase(), c.to_uppercase()),
+            false => c.to_string(),
+        }
+    }).collect();
+    dir.to_string() + &key
+}
+
 fn is_dir(s: &str, core: &mut ShellCore) -> bool {
     let tilde_prefix = "~/".to_string();
     let tilde_path = core.db.get_param("HOME").unwrap_or(String::new()) + "/";
@@ -50,73 +60,96 @@ fn is_dir(s: &str, core: &mut ShellCore) -> bool {
 
 impl Terminal {
     pub fn completion(&mut self, core: &mut ShellCore, tab_num: usize) {
+        let exit_status = core.db.exit_status;
+        self.complete_word(core, tab_num);
+        core.db.exit_status = exit_status;
+    }
+
+    fn complete_word(&mut self, core: &mut ShellCore, tab_num: usize) {
         self.escape_at_completion = true;
+        self.compspec = None;
         let _ = core.db.set_array("COMPREPLY", vec![], None);
-        self.set_completion_info(core);
+        self.set_completion_info(core, tab_num);
 
-        if ! Self::set_custom_compreply(core).is_ok()
-        && ! self.set_default_compreply(core).is_ok() {
+        if (self.set_spec_compreply(core).is_err()
+        && self.set_default_compreply(core).is_err())
+        || core.db.len("COMPREPLY") == 0 {
             self.cloop();
             return;
         }
 
         match tab_num  {
             1 => self.try_completion(core).unwrap(),
-            _ => self.show_list(&core.db.get_array_all("COMPREPLY"), tab_num),
+            _ => self.open_menu(core.db.get_array_all("COMPREPLY")),
         }
     }
 
-    fn set_custom_compreply(core: &mut ShellCore) -> Result<(), String> {
-        let cur_pos = Self::get_cur_pos(core);
-        let prev_pos = cur_pos - 1;
-        let word_num = core.db.len("COMP_WORDS") as i32;
-
-        if prev_pos < 0 || prev_pos >= word_num {
-            return Err("pos error".to_string());
+    /* the spec for the command, or for an empty or initial word, or complete -D */
+    fn find_spec(core: &mut ShellCore, com: &str, pos: &str, cur: &str) -> Option<CompSpec> {
+        if pos == "0" {
+            let key = if cur.is_empty() { compspec::EMPTY } else { compspec::INITIAL };
+            return core.completion_specs.get(key).cloned();
         }
 
-        let org_word = core.db.get_array_elem("COMP_WORDS", "0")?;
-        let prev_word = core.db.get_array_elem("COMP_WORDS", &prev_pos.to_string())?;
-        let cur_word = core.db.get_array_elem("COMP_WORDS", &cur_pos.to_string())?;
+        let basename = com.rsplit('/').next().unwrap_or(com);
+        [com, basename, compspec::DEFAULT].iter()
+            .find_map(|k| core.completion_specs.get(*k).cloned())
+    }
 
-        match core.completion_functions.get(&org_word) {
-            Some(value) => {
-                let command = format!("prev={} cur={} {}", &prev_word, &cur_word, &value);//TODO: cur should be set
-                let mut feeder = Feeder::new(&command);                              // by bash-completion 
+    fn set_spec_compreply(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
+        let pos = core.db.get_param("COMP_CWORD")?;
+        let com = core.db.get_array_elem("COMP_WORDS", "0").unwrap_or_default();
+        let cur: String = self.chars[self.completion_word.0..self.head].iter().collect();
+        let prev = match pos.parse::<usize>() {
+            Ok(n) if n > 0 => core.db.get_array_elem("COMP_WORDS", &(n-1).to_string()).unwrap_or_default(),
+            _ => String::new(),
+        };
 
-                if let Ok(Some(mut a)) = SimpleCommand::parse(&mut feeder, core) {
-                    let mut dummy = Pipe::new("".to_string());
-                    let _ = a.exec(core, &mut dummy);
-                }
-                match core.db.len("COMPREPLY") {
-                    0 => Err("no completion cand".to_string()),
-                    _ => Ok(()),
-                }
-            },
-            _ => Err("no completion function".to_string())
+        let mut spec = match Self::find_spec(core, &com, &pos, &cur) {
+            Some(s) => s,
+            None => return Err(ExecError::Other("no completion spec".to_string())),
+        };
+
+        /* a function exiting with 124 retries with the spec it has newly registered */
+        let mut cands = loop {
+            core.current_compspec = Some(spec.clone());
+            if let Some(cands) = completion::compgen_spec(core, &spec, &com, &cur, &prev) {
+                break cands;
+            }
+            match Self::find_spec(core, &com, &pos, &cur) {
+                Some(s) if s != spec => spec = s,
+                _ => break vec![],
+            }
+        };
+        let mut spec = core.current_compspec.take().unwrap_or(spec);
+
+        let mut args = vec!["".to_string(), "".to_string(), cur.clone()];
+        if spec.has("plusdirs") || (cands.is_empty() && spec.has("dirnames")) {
+            cands.append(&mut completion::compgen_d(core, &mut args));
         }
-    }
 
-    fn get_cur_pos(core: &mut ShellCore) -> i32 {
-        core.db.get_param("COMP_CWORD").unwrap().parse::<i32>().unwrap()
-            /*
-            Ok(i) => i,
-            _     => exit::internal("no COMP_CWORD"),
+        if cands.is_empty() && (spec.has("default") || spec.has("bashdefault")) {
+            spec.options.insert("filenames".to_string());
+            self.compspec = Some(spec);
+            return Err(ExecError::Other("fall back to the default".to_string()));
         }
-            */
+
+        if spec.has("noquote") {
+            self.escape_at_completion = false;
+        }
+        self.compspec = Some(spec);
+        core.db.set_array("COMPREPLY", cands, None)
     }
 
     pub fn set_default_compreply(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
         let pos = core.db.get_param("COMP_CWORD")?;
         let last = core.db.get_array_elem("COMP_WORDS", &pos)?;
 
-        let com = core.db.get_array_elem("COMP_WORDS", "0")?;
-
         let (tilde_prefix, tilde_path, last_tilde_expanded) = Self::set_tilde_transform(&last, core);
 
         let mut args = vec!["".to_string(), "".to_string(), last_tilde_expanded.to_string()];
 
-        let list = self.make_default_compreply(core, &mut args, &com, &pos);
+        let list = self.make_default_compreply(core, &mut args, &pos);
         if list.is_empty() {
             return Err(ExecError::Other("empty list".to_string()));
         }
@@ -126,33 +159,9 @@ impl Terminal {
     }
 
     fn make_default_compreply(&mut self, core: &mut ShellCore, args: &mut Vec<String>,
-                              com: &str, pos: &str) -> Vec<String> {
-        if core.completion_actions.contains_key(com) {
-            let (action, options) = core.completion_actions[com].clone();
-            let mut cands = match action.as_ref() {
-                "alias" => completion::compgen_a(core, args),
-                "command" => completion::compgen_c(core, args),
-                "job" => completion::compgen_j(core, args),
-                "setopt" => completion::compgen_o(core, args),
-                "stopped" => completion::compgen_stopped(core, args),
-                "user" => completion::compgen_u(core, args),
-                "variable" => completion::compgen_v(core, args),
-                _ => vec![],
-            };
-
-            if options.contains_key("-P") {
-                let prefix = &options["-P"];
-                cands = cands.iter().map(|c| prefix.clone() + c).collect();
-            }
-            if options.contains_key("-S") {
-                let suffix = &options["-S"];
-                cands = cands.iter().map(|c| c.to_owned() + &suffix.clone()).collect();
-            }
-            return cands;
-        }
-
+                              pos: &str) -> Vec<String> {
         if pos == "0" {
-            return if core.db.len("COMP_WORDS") == 0 {
+            return if core.db.get_param("COMP_LINE").unwrap_or_default().trim().is_empty() {
                 self.escape_at_completion = false;
                 completion::compgen_h(core, args).to_vec().into_iter().filter(|h| h.len() > 0).collect()
             }else{
@@ -160,116 +169,50 @@ impl Terminal {
             };
         }
 
+        if core.keymap.variable_on("completion-ignore-case") {
+            args[2] = ignore_case_pattern(&args[2]);
+        }
         completion::compgen_f(core, args)
     }
 
+    /* what follows a unique candidate */
+    pub fn completion_tail(&self, core: &mut ShellCore, cand: &str) -> &'static str {
+        let filenames = self.compspec.as_ref().is_none_or(|s| s.filenames());
+        let nospace = self.compspec.as_ref().is_some_and(|s| s.has("nospace"));
+        if filenames && is_dir(cand, core) {
+            "/"
+        }else if nospace {
+            ""
+        }else{
+            " "
+        }
+    }
+
     pub fn try_completion(&mut self, core: &mut ShellCore) -> Result<(), String> {
         let pos = core.db.get_param("COMP_CWORD")?;
         let target = core.db.get_array_elem("COMP_WORDS", &pos)?;
 
         if core.db.len("COMPREPLY") == 1 {
             let output = core.db.get_array_elem("COMPREPLY", "0")?;
-            let tail = match is_dir(&output, core) {
-                true  => "/",
-                false => " ",
-            };
+            let tail = self.completion_tail(core, &output);
             self.replace_input(&(output + tail));
             return Ok(());
         }
 
         let common = common_string(&core.db.get_array_all("COMPREPLY"));
-        if common.len() != target.len() {
+        let shrinks = common.len() < target.len() && core.keymap.variable_on("completion-ignore-case");
+        if common.len() != target.len() && ! shrinks {
             self.replace_input(&common);
             return Ok(());
         }
-        self.cloop();
-        Ok(())
-    }
-
-    fn normalize_tab(&mut self, row_num: i32, col_num: i32) {
-        let i = (self.tab_col*row_num + self.tab_row + row_num*col_num)%(row_num*col_num);
-        self.tab_col = i/row_num;
-        self.tab_row = i%row_num;
-    }
-
-    fn show_list(&mut self, list: &Vec<String>, tab_num: usize) {
-        if list.is_empty() {
-            return;
-        }
-        let widths: Vec<usize> = list.iter().map(|s| str_width(s)).collect();
-        let max_entry_width = widths.iter().max().unwrap_or(&1000) + 1;
-        let terminal_row_num = Terminal::size().1;
-        let col_num = std::cmp::min(
-                          std::cmp::max(Terminal::size().0 / max_entry_width, 1),
-                          list.len()
-                      );
-        let row_num = std::cmp::min(
-                          (list.len()-1) / col_num + 1,
-                          std::cmp::max(terminal_row_num - 2, 1)
-                      );
-        self.completion_candidate = String::new();
-
-        if tab_num > 2 {
-            self.normalize_tab(row_num as i32, col_num as i32);
-        }
-
-        eprintln!("\r");
-        for row in 0..row_num {
-            for col in 0..col_num {
-                let tab = self.tab_row == row as i32 && self.tab_col == col as i32;
-                self.print_an_entry(list, &widths, row, col, 
-                    row_num, max_entry_width, tab);
-            }
-            print!("\r\n");
-        }
-
-        let (cur_col, cur_row) = self.stdout.cursor_pos().unwrap();
-
-        self.check_scroll();
-        match cur_row as usize == terminal_row_num {
-            true => {
-                let back_row = std::cmp::max(cur_row as i16 - row_num as i16, 1);
-                self.write(&termion::cursor::Goto(cur_col, back_row as u16).to_string());
-                print!("\x1b[1A");
-                self.flush();
-            },
-            false => self.rewrite(false),
-        }
-    }
-
-    fn print_an_entry(&mut self, list: &Vec<String>, widths: &Vec<usize>,
-        row: usize, col: usize, row_num: usize, width: usize, pointed: bool) {
-        let i = col*row_num + row;
-        let space_num = match i < list.len() {
-            true  => width - widths[i],
-            false => width,
-        };
-        let cand = match i < list.len() {
-            true  => list[i].clone(),
-            false => "".to_string(),
-        };
-
-        let s = String::from_utf8(vec![b' '; space_num]).unwrap();
-        if pointed {
-            print!("\x1b[01;7m{}{}\x1b[00m", &cand, &s);
-            self.completion_candidate = cand;
-        }else{
-            print!("{}{}", &cand, &s);
+        match core.keymap.variable_on("show-all-if-ambiguous") {
+            true  => self.open_menu(core.db.get_array_all("COMPREPLY")),
+            false => self.cloop(),
         }
+        Ok(())
     }
 
     pub fn replace_input(&mut self, to: &String) {
-        while self.head > self.prompt.chars().count() 
-        && ( self.head > 0 && self.chars[self.head-1] != ' ' ||
-           (self.head > 1 && self.chars[self.head-1] == ' ' 
-            && self.chars[self.head-2] == '\\') ) {
-            self.backspace();
-        }
-        while self.head < self.chars.len() 
-        && self.chars[self.head] != ' ' {
-            self.delete();
-        }
-
         let to_escaped = if to.ends_with(" ") {
             let mut tmp = to.to_string();
             tmp.pop();
@@ -284,11 +227,7 @@ impl Terminal {
             }
         };
 
-        for c in to_escaped.chars() {
-            self.insert(c);
-            self.check_scroll();
-        }
-
+        self.replace_word(&to_escaped);
         if to.ends_with(" ") 
         && self.head < self.chars.len() 
         && self.chars[self.head] == ' ' {
@@ -298,6 +237,23 @@ impl Terminal {
         self.rewrite(true);
     }
 
+    /* replaces the word under completion with the text as it is */
+    pub fn replace_word(&mut self, to: &str) {
+        let (start, end) = self.completion_word;
+        for _ in self.head..end {
+            self.delete();
+        }
+        for _ in start..self.head {
+            self.backspace();
+        }
+
+        for c in to.chars() {
+            self.insert(c);
+            self.check_scroll();
+        }
+        self.completion_word = (start, self.head);
+    }
+
     fn set_tilde_transform(last: &str, core: &mut ShellCore) -> (String, String, String) {
         let tilde_prefix;
         let tilde_path;
@@ -316,37 +272,99 @@ impl Terminal {
         (tilde_prefix, tilde_path, last_tilde_expanded)
     }
 
-    fn set_completion_info(&mut self, core: &mut ShellCore){
+    fn set_completion_info(&mut self, core: &mut ShellCore, tab_num: usize) {
         let prompt = self.prompt.chars().count();
-        let all_string = self.get_string(prompt);
-        let mut words_all = utils::split_words(&all_string);
-        words_all.retain(|e| e != "");
-
-        let left_string: String = self.chars[prompt..self.head].iter().collect();
-        let mut words_left = utils::split_words(&left_string);
-        words_left.retain(|e| e != "");
-        let from = completion_from(&words_left, core);
-
-        words_all = words_all[from..].to_vec();
-        words_left = words_left[from..].to_vec();
-        let _ = core.db.set_array("COMP_WORDS", words_all, None);
-
-        let mut num = words_left.len();
-        match left_string.chars().last() {
-            Some(' ') => {},
-            Some(_) => {
-                if num > 0 {
-                    num -= 1
-                }
+        let line = self.chars[prompt..].to_vec();
+        let point = self.head - prompt;
+        let breaks = core.db.get_param("COMP_WORDBREAKS").unwrap_or_default();
+
+        let mut ranges = split_comp_words(&line, &breaks);
+        /* a run of word break characters just before the cursor is not completed */
+        let cword = match ranges.iter().rposition(|(s, e, brk)| *s <= point && point <= *e
+                                                  && ! (*brk && *e == point)) {
+            Some(i) => i,
+            None => {
+                let i = ranges.iter().filter(|(_, e, _)| *e <= point).count();
+                ranges.insert(i, (point, point, false));
+                i
             },
+        };
+        let words: Vec<String> = ranges.iter().map(|(s, e, _)| line[*s..*e].iter().collect()).collect();
+        let left = match words[cword].is_empty() {
+            true  => &words[..cword],
+            false => &words[..cword+1],
+        };
+        let from = std::cmp::min(completion_from(left, core), cword);
+
+        let (start, end, _) = ranges[cword];
+        self.completion_word = (prompt + start, prompt + end);
+
+        let line_start = ranges[from].0;
+        let comp_line: String = line[line_start..].iter().collect();
+        let comp_type = if tab_num > 1 { "63" } else { "9" };
+        let _ = core.db.set_array("COMP_WORDS", words[from..].to_vec(), None);
+        let _ = core.db.set_param("COMP_CWORD", &(cword - from).to_string(), None);
+        let _ = core.db.set_param("COMP_LINE", &comp_line, None);
+        let _ = core.db.set_param("COMP_POINT", &(point - line_start).to_string(), None);
+        let _ = core.db.set_param("COMP_TYPE", comp_type, None);
+        let _ = core.db.set_param("COMP_KEY", "9", None);
+    }
+}
+
+/* splits a line into ranges of words at blanks and at COMP_WORDBREAKS characters
+ * with flags telling whether they are runs of the break characters */
+fn split_comp_words(line: &[char], breaks: &str) -> Vec<(usize, usize, bool)> {
+    let is_break = |c: char| breaks.contains(c) && ! " \t\n'\"".contains(c);
+    let mut ans = vec![];
+    let mut start = None;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in line.iter().enumerate() {
+        let c = *c;
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }else if c == '\\' && q == '"' {
+                escaped = true;
+            }
+            continue;
+        }
+
+        if c == ' ' || c == '\t' || c == '\n' || is_break(c) {
+            if let Some(s) = start.take() {
+                ans.push((s, i, false));
+            }
+            if is_break(c) {
+                match ans.last_mut() {
+                    Some(w) if w.2 && w.1 == i => w.1 = i + 1,
+                    _ => ans.push((i, i + 1, true)),
+                }
+            }
+            continue;
+        }
+
+        if start.is_none() {
+            start = Some(i);
+        }
+        match c {
+            '\\' => escaped = true,
+            '\'' | '"' => quote = Some(c),
             _ => {},
         }
+    }
 
-        let _ = core.db.set_param("COMP_CWORD", &num.to_string(), None);
+    if let Some(s) = start {
+        ans.push((s, line.len(), false));
     }
+    ans
 }
 
-fn completion_from(ws: &Vec<String>, core: &mut ShellCore) -> usize {
+fn completion_from(ws: &[String], core: &mut ShellCore) -> usize {
     for i in (0..ws.len()).rev() {
         if utils::reserved(&ws[i]) {
             continue;
@@ -0,0 +1,363 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{Feeder, Script, ShellCore};
+use crate::feeder::terminal::Terminal;
+use crate::utils::file;
+use std::io::Stdin;
+use termion::cursor::DetectCursorPos;
+use termion::event;
+use termion::input::Keys;
+
+pub enum EditResult {
+    Continue,
+    Accept,
+    Eof,
+}
+
+/* the raw key sequence of a key event, in the form of keymap entries */
+pub fn keyseq(key: &event::Key) -> String {
+    match key {
+        event::Key::Backspace => "\x7f".to_string(),
+        event::Key::Left      => "\x1b[D".to_string(),
+        event::Key::Right     => "\x1b[C".to_string(),
+        event::Key::Up        => "\x1b[A".to_string(),
+        event::Key::Down      => "\x1b[B".to_string(),
+        event::Key::Home      => "\x1b[H".to_string(),
+        event::Key::End       => "\x1b[F".to_string(),
+        event::Key::PageUp    => "\x1b[5~".to_string(),
+        event::Key::PageDown  => "\x1b[6~".to_string(),
+        event::Key::BackTab   => "\x1b[Z".to_string(),
+        event::Key::Delete    => "\x1b[3~".to_string(),
+        event::Key::Insert    => "\x1b[2~".to_string(),
+        event::Key::Char('\n') => "\r".to_string(),
+        event::Key::Char(c)   => c.to_string(),
+        event::Key::Alt(c)    => format!("\x1b{}", c),
+        event::Key::Ctrl(c @ '4'..='7') => ((*c as u8 - b'4' + 0x1c) as char).to_string(),
+        event::Key::Ctrl(c)   => (((*c as u8) & 0x1f) as char).to_string(),
+        event::Key::Null      => "\0".to_string(),
+        event::Key::Esc       => "\x1b".to_string(),
+        _ => String::new(),
+    }
+}
+
+/* the key events that reproduce the characters of a macro */
+pub fn macro_keys(s: &str) -> Vec<event::Key> {
+    s.chars().map(|c| match c {
+        '\x1b' => event::Key::Esc,
+        '\x7f' => event::Key::Backspace,
+        '\r' | '\n' => event::Key::Char('\n'),
+        '\t' => event::Key::Char('\t'),
+        '\0' => event::Key::Null,
+        c if (c as u32) < 0x20 => event::Key::Ctrl(((c as u8) + 0x60) as char),
+        c => event::Key::Char(c),
+    }).collect()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Terminal {
+    pub fn prompt_len(&self) -> usize {
+        self.prompt.chars().count()
+    }
+
+    fn forward_word_pos(&self) -> usize {
+        let mut pos = self.head;
+        while pos < self.chars.len() && ! is_word_char(self.chars[pos]) {
+            pos += 1;
+        }
+        while pos < self.chars.len() && is_word_char(self.chars[pos]) {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn backward_word_pos(&self, is_word: fn(char) -> bool) -> usize {
+        let min = self.prompt_len();
+        let mut pos = self.head;
+        while pos > min && ! is_word(self.chars[pos-1]) {
+            pos -= 1;
+        }
+        while pos > min && is_word(self.chars[pos-1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    pub fn move_to(&mut self, pos: usize) {
+        self.head = pos;
+        self.goto(self.head);
+        self.flush();
+    }
+
+    pub fn kill(&mut self, core: &mut ShellCore, from: usize, to: usize) {
+        if from >= to {
+            return;
+        }
+        core.kill_buffer = self.chars.drain(from..to).collect();
+        self.head = from;
+        self.rewrite(true);
+    }
+
+    fn change_word(&mut self, f: fn(usize, char) -> String) {
+        let end = self.forward_word_pos();
+        let mut n = 0;
+        let mut changed = vec![];
+        for c in &self.chars[self.head..end] {
+            match is_word_char(*c) {
+                true  => { changed.extend(f(n, *c).chars()); n += 1; },
+                false => changed.push(*c),
+            }
+        }
+        self.chars.splice(self.head..end, changed);
+        self.head = end;
+        self.rewrite(true);
+    }
+
+    fn transpose_chars(&mut self) {
+        let min = self.prompt_len();
+        if self.chars.len() < min + 2 || self.head == min {
+            self.cloop();
+            return;
+        }
+        if self.head == self.chars.len() {
+            self.head -= 1;
+        }
+        self.chars.swap(self.head - 1, self.head);
+        self.head += 1;
+        self.rewrite(true);
+    }
+
+    fn history_search(&mut self, core: &mut ShellCore, older: bool) {
+        let prefix: String = self.chars[self.prompt_len()..self.head].iter().collect();
+        let head = self.head;
+        let len = core.history.len() as i32;
+        let mut ptr = self.hist_ptr as i32;
+
+        loop {
+            ptr += if older { 1 } else { -1 };
+            if ptr < 0 || ptr >= len {
+                self.cloop();
+                return;
+            }
+            if core.history[ptr as usize].starts_with(&prefix)
+            && core.history[ptr as usize] != core.history[self.hist_ptr] {
+                break;
+            }
+        }
+
+        self.call_history(ptr - self.hist_ptr as i32, core);
+        self.head = head;
+        self.goto(self.head);
+        self.flush();
+    }
+
+    fn clear_screen(&mut self) {
+        self.write(termion::clear::All.as_ref());
+        self.write(&termion::cursor::Goto(1, 1).to_string());
+        self.prompt_row = 1;
+        self.rewrite(true);
+    }
+
+    fn complete(&mut self, core: &mut ShellCore, last_function: &str) {
+        if self.menu.is_some() {
+            self.menu_move(1);
+            return;
+        }
+        if self.tab_num == 0 || last_function == "complete" {
+            self.tab_num += 1;
+        }
+        let tab_num = self.tab_num;
+        self.completion(core, tab_num);
+    }
+
+    pub fn undo(&mut self) {
+        match self.undo_list.pop() {
+            Some((chars, head)) => {
+                self.chars = chars;
+                self.head = head;
+                self.rewrite(true);
+            },
+            None => self.cloop(),
+        }
+    }
+
+    fn set_editing_mode(&mut self, core: &mut ShellCore, mode: &str) {
+        core.keymap.set_editing_mode(mode);
+        core.sync_editing_mode();
+        self.vi.command_mode = false;
+    }
+
+    fn accept_line(&mut self, core: &mut ShellCore) -> EditResult {
+        if self.menu_selected() {
+            self.finish_menu(core);
+            return EditResult::Continue;
+        }
+        self.close_menu();
+
+        self.erase_suggestion();
+        self.goto(self.chars.len());
+        self.write("\r\n");
+        self.chars.push('\n');
+        EditResult::Accept
+    }
+
+    pub fn call_function(&mut self, core: &mut ShellCore, name: &str, typed: &str,
+                         last_function: &str, keys: &mut Keys<Stdin>) -> EditResult {
+        let min = self.prompt_len();
+        match name {
+            "end-of-line" | "forward-char" if self.accept_suggestion(false) => {},
+            "forward-word" if self.accept_suggestion(true) => {},
+            "abort" if self.menu.is_some() => self.cancel_menu(),
+            "abort" => self.cloop(),
+            "accept-line" => return self.accept_line(core),
+            "backward-char" => self.shift_cursor(-1),
+            "backward-delete-char" => self.backspace(),
+            "backward-kill-line" | "unix-line-discard" => self.kill(core, min, self.head),
+            "backward-kill-word" => {
+                let pos = self.backward_word_pos(is_word_char);
+                self.kill(core, pos, self.head);
+            },
+            "backward-word" => self.move_to(self.backward_word_pos(is_word_char)),
+            "beginning-of-history" => {
+                let last = core.history.len() as i32 - 1;
+                self.call_history(last - self.hist_ptr as i32, core);
+            },
+            "beginning-of-line" => self.goto_origin(),
+            "capitalize-word" => self.change_word(|n, c| match n {
+                0 => c.to_uppercase().to_string(),
+                _ => c.to_lowercase().to_string(),
+            }),
+            "clear-screen" => self.clear_screen(),
+            "complete" => self.complete(core, last_function),
+            "delete-char" => {
+                if self.chars.len() == min {
+                    self.write("\r\n");
+                    return EditResult::Eof;
+                }
+                self.delete();
+            },
+            "downcase-word" => self.change_word(|_, c| c.to_lowercase().to_string()),
+            "edit-and-execute-command" => {
+                if self.edit_and_execute(core) {
+                    return self.accept_line(core);
+                }
+            },
+            "emacs-editing-mode" => self.set_editing_mode(core, "emacs"),
+            "end-of-history" => self.call_history(- (self.hist_ptr as i32), core),
+            "end-of-line" => self.goto_end(),
+            "forward-char" => self.shift_cursor(1),
+            "forward-search-history" | "reverse-search-history" => {
+                let forward = name == "forward-search-history";
+                if let Some(k) = self.incremental_search(core, forward, keys) {
+                    self.pending.push_front(k);
+                }
+            },
+            "forward-word" => self.move_to(self.forward_word_pos()),
+            "history-search-backward" => self.history_search(core, true),
+            "history-search-forward" => self.history_search(core, false),
+            "kill-line" => self.kill(core, self.head, self.chars.len()),
+            "kill-whole-line" => self.kill(core, min, self.chars.len()),
+            "kill-word" => self.kill(core, self.head, self.forward_word_pos()),
+            "next-history" => self.call_history(-1, core),
+            "menu-complete" => self.menu_complete(core, 1),
+            "menu-complete-backward" => self.menu_complete(core, -1),
+            "possible-completions" => {
+                self.close_menu();
+                self.completion(core, 2);
+            },
+            "previous-history" => self.call_history(1, core),
+            "quoted-insert" => {
+                if let Some(k) = self.next_key(keys) {
+                    for c in keyseq(&k).chars() {
+                        self.insert(c);
+                    }
+                }
+            },
+            "redraw-current-line" => self.rewrite(true),
+            "self-insert" => typed.chars().for_each(|c| self.insert(c)),
+            "tab-insert" => self.insert('\t'),
+            "transpose-chars" => self.transpose_chars(),
+            "undo" => self.undo(),
+            "unix-word-rubout" => {
+                let pos = self.backward_word_pos(|c| ! c.is_whitespace());
+                self.kill(core, pos, self.head);
+            },
+            "upcase-word" => self.change_word(|_, c| c.to_uppercase().to_string()),
+            "vi-editing-mode" => self.set_editing_mode(core, "vi"),
+            "yank" => core.kill_buffer.clone().chars().for_each(|c| self.insert(c)),
+            name if name.starts_with("vi-") => return self.vi_function(core, name, typed, keys),
+            _ => self.cloop(),
+        }
+        EditResult::Continue
+    }
+
+    /* runs a command line with the terminal in cooked mode */
+    fn run_cooked(&mut self, core: &mut ShellCore, command: &str) {
+        self.goto(self.chars.len());
+        self.write("\r\n");
+        self.flush();
+        let _ = self.stdout.suspend_raw_mode();
+
+        let mut feeder = Feeder::new(&(command.to_string() + "\n"));
+        match Script::parse(&mut feeder, core, false) {
+            Ok(Some(mut s)) => {let _ = s.exec(core); },
+            Err(e) => e.print(core),
+            _ => {},
+        }
+
+        let _ = self.stdout.activate_raw_mode();
+        self.prompt_row = self.stdout.cursor_pos().unwrap_or((1,1)).1 as usize;
+    }
+
+    fn set_line(&mut self, line: &str, point: usize) {
+        let min = self.prompt_len();
+        self.chars = self.prompt.chars().chain(line.chars()).collect();
+        self.head = std::cmp::min(min.saturating_add(point), self.chars.len());
+        self.rewrite(true);
+    }
+
+    pub fn execute_command(&mut self, core: &mut ShellCore, command: &str) {
+        let min = self.prompt_len();
+        let line = self.get_string(min);
+        let point = self.head - min;
+        let _ = core.db.set_param("READLINE_LINE", &line, None);
+        let _ = core.db.set_param("READLINE_POINT", &point.to_string(), None);
+
+        self.run_cooked(core, command);
+
+        let line = core.db.get_param("READLINE_LINE").unwrap_or_default();
+        let point = core.db.get_param("READLINE_POINT").unwrap_or_default()
+                    .parse::<usize>().unwrap_or(line.chars().count());
+        core.db.unset("READLINE_LINE");
+        core.db.unset("READLINE_POINT");
+        self.set_line(&line, point);
+    }
+
+    /* edits the line with $VISUAL or $EDITOR and returns true if it should be executed */
+    fn edit_and_execute(&mut self, core: &mut ShellCore) -> bool {
+        let line = self.get_string(self.prompt_len()) + "\n";
+        let editor = file::editor(core, &["VISUAL", "EDITOR"]);
+        let run = |core: &mut ShellCore, command: &str| {
+            self.run_cooked(core, command);
+            core.db.exit_status
+        };
+
+        let edited = match file::edit_text(core, &editor, &line, run) {
+            Ok((edited, 0)) => edited,
+            Ok(_) => {
+                self.rewrite(true);
+                return false;
+            },
+            Err(_) => {
+                self.cloop();
+                return false;
+            },
+        };
+        let edited = edited.trim_end_matches('\n');
+        self.set_line(edited, edited.chars().count());
+        true
+    }
+}
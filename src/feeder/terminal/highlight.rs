@@ -0,0 +1,179 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{file_check, utils, Feeder, ShellCore, Script};
+use crate::error::parse::{ParseError, SourcePos};
+use crate::feeder::Token;
+use crate::feeder::terminal::Terminal;
+use crate::utils::file;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Style {
+    Plain,
+    Keyword,
+    Builtin,
+    Function,
+    Alias,
+    External,
+    NotFound,
+    Str,
+    Variable,
+    Comment,
+    Redirect,
+    Unmatched,
+}
+
+impl Style {
+    fn escape(&self) -> &'static str {
+        match self {
+            Style::Plain     => "\x1b[00m",
+            Style::Keyword   => "\x1b[01;33m",
+            Style::Builtin   => "\x1b[01;32m",
+            Style::Function  => "\x1b[34m",
+            Style::Alias     => "\x1b[36m",
+            Style::External  => "\x1b[32m",
+            Style::NotFound  => "\x1b[31m",
+            Style::Str       => "\x1b[33m",
+            Style::Variable  => "\x1b[35m",
+            Style::Comment   => "\x1b[90m",
+            Style::Redirect  => "\x1b[01m",
+            Style::Unmatched => "\x1b[41m",
+        }
+    }
+}
+
+/* the index of the character at a position in an error message */
+fn char_index(chars: &[char], pos: &SourcePos) -> usize {
+    let lines: usize = chars.split(|c| *c == '\n').take(pos.lineno - 1).map(|ln| ln.len() + 1).sum();
+    std::cmp::min(lines + pos.column - 1, chars.len())
+}
+
+fn token_style(token: Token) -> Style {
+    match token {
+        Token::Keyword  => Style::Keyword,
+        Token::Command  => Style::Plain,
+        Token::Str      => Style::Str,
+        Token::Variable => Style::Variable,
+        Token::Comment  => Style::Comment,
+        Token::Redirect => Style::Redirect,
+    }
+}
+
+impl Terminal {
+    fn command_style(&mut self, core: &mut ShellCore, word: &str) -> Style {
+        if let Some(s) = self.command_styles.get(word) {
+            return *s;
+        }
+
+        let style = if core.aliases.contains_key(word) {
+            Style::Alias
+        }else if core.db.functions.contains_key(word) {
+            Style::Function
+        }else if core.builtins.contains_key(word) {
+            Style::Builtin
+        }else if word.contains('/') {
+            match file_check::is_executable(word) && ! file_check::is_dir(word) {
+                true  => Style::External,
+                false => Style::NotFound,
+            }
+        }else if file::search_command(word).is_some() {
+            Style::External
+        }else{
+            Style::NotFound
+        };
+
+        self.command_styles.insert(word.to_string(), style);
+        style
+    }
+
+    /* parses the input without executing it and paints the tokens the parser records */
+    fn styles(&mut self, core: &mut ShellCore, chars: &[char]) -> Vec<Style> {
+        let text: String = chars.iter().collect();
+        let mut styles = vec![Style::Plain; chars.len()];
+        let mut feeder = Feeder::new(&text);
+        feeder.record_tokens();
+
+        let exit_status = core.db.exit_status;
+        let lineno = core.db.get_param("LINENO").unwrap_or_default();
+        let mut error = None;
+        while feeder.len() > 0 {
+            let len = feeder.len();
+            match Script::parse(&mut feeder, core, false) {
+                Ok(Some(_)) if feeder.len() < len => {},
+                Ok(_) => break,
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                },
+            }
+        }
+        core.db.exit_status = exit_status;
+        let _ = core.db.set_param("LINENO", &lineno, None);
+
+        let mut tokens = feeder.tokens.take().unwrap_or_default();
+        tokens.sort_by_key(|t| (t.0, usize::MAX - t.1));
+        for (from, to, token) in tokens {
+            let from = text[..from].chars().count();
+            let to = std::cmp::min(text[..to].chars().count(), chars.len());
+            let style = match token {
+                Token::Command => {
+                    let word: String = chars[from..to].iter().collect();
+                    match word.contains(|c| "'\"\\$`".contains(c)) {
+                        true  => continue,
+                        false => self.command_style(core, &word),
+                    }
+                },
+                t => token_style(t),
+            };
+            styles[from..to].iter_mut().for_each(|s| *s = style);
+        }
+
+        let unmatched = match &error {
+            Some(ParseError::UnterminatedQuote(_, pos)) => Some((pos, chars.len())),
+            Some(ParseError::MissingTerminator(opener, _, pos)) if ! utils::reserved(opener) => Some((pos, chars.len())),
+            Some(ParseError::UnexpectedSymbol(_, pos)) | Some(ParseError::BadRedirect(_, pos)) => {
+                let from = char_index(chars, pos);
+                Some((pos, from + std::cmp::max(pos.len, 1)))
+            },
+            _ => None,
+        };
+        if let Some((pos, to)) = unmatched {
+            let from = char_index(chars, pos);
+            let to = std::cmp::min(to, chars.len());
+            if from < to {
+                styles[from..to].iter_mut().for_each(|s| *s = Style::Unmatched);
+            }
+        }
+        styles
+    }
+
+    /* redraws the input with colors when the shopt syntax_highlight is on */
+    pub fn highlight(&mut self, core: &mut ShellCore) {
+        let min = self.prompt.chars().count();
+        if ! core.shopts.query("syntax_highlight") || self.chars[min..] == self.highlighted[..] {
+            return;
+        }
+        self.highlighted = self.chars[min..].to_vec();
+
+        let input = self.highlighted.clone();
+        let styles = self.styles(core, &input);
+        let mut out = String::new();
+        let mut prev = Style::Plain;
+        for (c, s) in input.iter().zip(styles) {
+            if s != prev {
+                out += s.escape();
+                prev = s;
+            }
+            match c {
+                '\n' => out += &format!("{}\n\r{}", Style::Plain.escape(), s.escape()),
+                c => out.push(*c),
+            }
+        }
+        out += Style::Plain.escape();
+
+        self.goto(min);
+        self.write(&out);
+        self.goto(self.head);
+        self.flush();
+    }
+}
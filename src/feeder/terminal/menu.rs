@@ -0,0 +1,187 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::feeder::terminal::Terminal;
+use termion::event;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+pub struct Menu {
+    cands: Vec<String>,
+    selected: Option<usize>,
+    original: String,
+}
+
+fn display_form(cand: &str) -> String {
+    cand.replace("↵ \0", "↵")
+}
+
+/* cuts a string so that it fits in the width */
+fn fit(s: &str, width: usize) -> String {
+    let mut w = 0;
+    s.chars().take_while(|c| {
+        w += UnicodeWidthChar::width(*c).unwrap_or(0);
+        w <= width
+    }).collect()
+}
+
+impl Terminal {
+    /* the numbers of columns and rows of the grid, and the width of a column */
+    fn menu_grid(&self, cands: &[String]) -> (usize, usize, usize) {
+        let term_width = std::cmp::max(Terminal::size().0, 2);
+        let width = cands.iter().map(|c| UnicodeWidthStr::width(display_form(c).as_str()))
+                    .max().unwrap_or(0) + 2;
+        let width = std::cmp::min(width, term_width - 1);
+        let cols = (term_width / width).clamp(1, cands.len());
+        (cols, cands.len().div_ceil(cols), width)
+    }
+
+    fn draw_menu(&mut self) {
+        let (cands, selected) = match &self.menu {
+            Some(m) => (m.cands.clone(), m.selected),
+            None => return,
+        };
+        let (cols, rows, width) = self.menu_grid(&cands);
+
+        /* the rows below the input are paged when they are too many */
+        let input_lines = self.head_to_cursor_pos(self.chars.len(), 0).1 + 1;
+        let room = std::cmp::max(Terminal::size().1.saturating_sub(input_lines), 1);
+        let page_rows = match rows > room {
+            true  => std::cmp::max(room - 1, 1),
+            false => rows,
+        };
+        let first = selected.map_or(0, |i| i % rows) / page_rows * page_rows;
+        let last = std::cmp::min(first + page_rows, rows);
+
+        let mut out = String::new();
+        for row in first..last {
+            out += "\r\n";
+            for col in 0..cols {
+                let i = col * rows + row;
+                if i >= cands.len() {
+                    break;
+                }
+                let entry = fit(&display_form(&cands[i]), width - 1);
+                let pad = width - UnicodeWidthStr::width(entry.as_str());
+                match selected == Some(i) {
+                    true  => out += &format!("\x1b[01;7m{}\x1b[00m{}", entry, " ".repeat(pad)),
+                    false => out += &format!("{}{}", entry, " ".repeat(pad)),
+                }
+            }
+        }
+        let mut lines = last - first;
+        if page_rows < rows {
+            let status = format!("-- rows {} to {} of {} --", first + 1, last, rows);
+            out += &format!("\r\n\x1b[7m{}\x1b[00m", fit(&status, Terminal::size().0 - 1));
+            lines += 1;
+        }
+
+        self.goto(self.chars.len());
+        self.write(termion::clear::AfterCursor.as_ref());
+        self.write(&out);
+
+        let bottom = self.prompt_row + input_lines - 1 + lines;
+        let term_rows = Terminal::size().1;
+        if bottom > term_rows {
+            self.prompt_row = std::cmp::max(self.prompt_row as isize - (bottom - term_rows) as isize, 1) as usize;
+        }
+        self.goto(self.head);
+        self.flush();
+    }
+
+    /* shows the candidates under the input without selecting any of them */
+    pub fn open_menu(&mut self, cands: Vec<String>) {
+        if cands.is_empty() {
+            return;
+        }
+        let (start, end) = self.completion_word;
+        let original = self.chars[start..end].iter().collect();
+        self.menu = Some(Menu { cands, selected: None, original });
+        self.draw_menu();
+    }
+
+    fn select_in_menu(&mut self, pos: usize) {
+        let cand = match self.menu.as_mut() {
+            Some(m) => {
+                m.selected = Some(pos);
+                m.cands[pos].clone()
+            },
+            None => return,
+        };
+        self.replace_input(&cand);
+        self.draw_menu();
+    }
+
+    pub fn menu_move(&mut self, shift: isize) {
+        let (len, selected) = match &self.menu {
+            Some(m) => (m.cands.len() as isize, m.selected),
+            None => return,
+        };
+        let pos = match selected {
+            Some(i) => (i as isize + shift).rem_euclid(len),
+            None if shift > 0 => 0,
+            None => len - 1,
+        };
+        self.select_in_menu(pos as usize);
+    }
+
+    pub fn menu_arrow(&mut self, key: &event::Key) {
+        let rows = match &self.menu {
+            Some(m) => self.menu_grid(&m.cands).1 as isize,
+            None => return,
+        };
+        match key {
+            event::Key::Down  => self.menu_move(1),
+            event::Key::Up    => self.menu_move(-1),
+            event::Key::Right => self.menu_move(rows),
+            event::Key::Left  => self.menu_move(-rows),
+            _ => {},
+        }
+    }
+
+    pub fn menu_complete(&mut self, core: &mut ShellCore, shift: isize) {
+        if self.menu.is_none() {
+            self.completion(core, 2);
+        }
+        self.menu_move(shift);
+    }
+
+    pub fn menu_selected(&self) -> bool {
+        self.menu.as_ref().is_some_and(|m| m.selected.is_some())
+    }
+
+    /* fixes the selected candidate with the same tail as a unique completion */
+    pub fn finish_menu(&mut self, core: &mut ShellCore) {
+        let cand = match self.menu.as_ref().and_then(|m| m.selected.map(|i| m.cands[i].clone())) {
+            Some(c) => c,
+            None => return,
+        };
+        self.close_menu();
+        let tail = self.completion_tail(core, &cand);
+        self.replace_input(&(cand + tail));
+    }
+
+    /* puts back the word before the menu */
+    pub fn cancel_menu(&mut self) {
+        let original = match self.menu.as_ref() {
+            Some(m) if m.selected.is_some() => m.original.clone(),
+            _ => {
+                self.close_menu();
+                return;
+            },
+        };
+        self.close_menu();
+        self.replace_word(&original);
+        self.rewrite(true);
+    }
+
+    pub fn close_menu(&mut self) {
+        if self.menu.take().is_none() {
+            return;
+        }
+        self.goto(self.chars.len());
+        self.write(termion::clear::AfterCursor.as_ref());
+        self.goto(self.head);
+        self.flush();
+    }
+}
@@ -0,0 +1,192 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::feeder::terminal::Terminal;
+use std::io::Stdin;
+use termion::event;
+use termion::input::Keys;
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Clone)]
+struct SearchState {
+    query: String,
+    index: usize,
+    pos: usize,
+    failed: bool,
+}
+
+fn entry(core: &ShellCore, index: usize) -> String {
+    core.history[index].replace("↵ \0", "\n")
+}
+
+/* char positions of the query in the entry */
+fn positions(entry: &str, query: &str) -> Vec<usize> {
+    entry.match_indices(query)
+         .map(|(i, _)| entry[..i].chars().count())
+         .collect()
+}
+
+fn find(core: &ShellCore, state: &SearchState, forward: bool, skip_current: bool) -> Option<(usize, usize)> {
+    if state.query.is_empty() {
+        return Some((state.index, state.pos));
+    }
+
+    let current = entry(core, state.index);
+    let in_current = positions(&current, &state.query);
+    let pos = match (forward, skip_current) {
+        (false, false) => in_current.iter().rev().find(|p| **p <= state.pos),
+        (false, true)  => in_current.iter().rev().find(|p| **p < state.pos),
+        (true, false)  => in_current.iter().find(|p| **p >= state.pos),
+        (true, true)   => in_current.iter().find(|p| **p > state.pos),
+    };
+    if let Some(p) = pos {
+        return Some((state.index, *p));
+    }
+
+    let indexes: Vec<usize> = match forward {
+        true  => (0..state.index).rev().collect(),
+        false => (state.index+1..core.history.len()).collect(),
+    };
+
+    for i in indexes {
+        let e = entry(core, i);
+        if skip_current && e == current {
+            continue;
+        }
+        let found = positions(&e, &state.query);
+        let pos = match forward {
+            true  => found.first(),
+            false => found.last(),
+        };
+        if let Some(p) = pos {
+            return Some((i, *p));
+        }
+    }
+    None
+}
+
+impl Terminal {
+    pub fn incremental_search(&mut self, core: &mut ShellCore, forward: bool,
+                              keys: &mut Keys<Stdin>) -> Option<event::Key> {
+        let prompt = self.prompt.clone();
+        let width_map = self.prompt_width_map.clone();
+        let prompt_len = prompt.chars().count();
+        let original = self.get_string(prompt_len);
+        let original_head = self.head;
+
+        if self.hist_ptr < core.history.len() {
+            core.history[self.hist_ptr] = original.replace("\n", "↵ \0");
+        }
+
+        let mut forward = forward;
+        let mut state = SearchState {
+            query: String::new(),
+            index: std::cmp::min(self.hist_ptr, core.history.len() - 1),
+            pos: self.head - prompt_len,
+            failed: false,
+        };
+        let mut stack = vec![];
+        let mut aborted = false;
+        let mut next_key = None;
+
+        self.draw_search(core, &state, forward);
+        while let Some(Ok(key)) = keys.next() {
+            match key {
+                event::Key::Ctrl('r') | event::Key::Ctrl('s') => {
+                    forward = key == event::Key::Ctrl('s');
+                    stack.push(state.clone());
+                    match find(core, &state, forward, true) {
+                        Some((i, p)) => (state.index, state.pos, state.failed) = (i, p, false),
+                        None => {
+                            state.failed = true;
+                            self.cloop();
+                        },
+                    }
+                },
+                event::Key::Ctrl('g') => {
+                    aborted = true;
+                    break;
+                },
+                event::Key::Backspace => {
+                    match stack.pop() {
+                        Some(s) => state = s,
+                        None    => self.cloop(),
+                    }
+                },
+                event::Key::Esc => break,
+                event::Key::Char(c) if c != '\n' && c != '\t' => {
+                    stack.push(state.clone());
+                    state.query.push(c);
+                    match find(core, &state, forward, false) {
+                        Some((i, p)) => (state.index, state.pos, state.failed) = (i, p, false),
+                        None => {
+                            state.failed = true;
+                            self.cloop();
+                        },
+                    }
+                },
+                _ => {
+                    next_key = Some(key);
+                    break;
+                },
+            }
+            self.draw_search(core, &state, forward);
+        }
+
+        self.prompt = prompt;
+        self.prompt_width_map = width_map;
+        self.chars = self.prompt.chars().collect();
+        match aborted {
+            true => {
+                self.chars.extend(original.chars());
+                self.head = original_head;
+            },
+            false => {
+                self.chars.extend(entry(core, state.index).chars());
+                self.head = prompt_len + state.pos;
+                self.hist_ptr = state.index;
+            },
+        }
+        self.rewrite(true);
+        next_key
+    }
+
+    fn draw_search(&mut self, core: &ShellCore, state: &SearchState, forward: bool) {
+        let label = match (state.failed, forward) {
+            (false, false) => "(reverse-i-search)",
+            (false, true)  => "(i-search)",
+            (true, false)  => "(failed reverse-i-search)",
+            (true, true)   => "(failed i-search)",
+        };
+        let mini_prompt = format!("{}`{}': ", label, &state.query);
+        let found = entry(core, state.index);
+
+        self.prompt_width_map = mini_prompt.chars()
+                                .map(|c| UnicodeWidthChar::width(c).unwrap_or(0))
+                                .collect();
+        self.prompt = mini_prompt.clone();
+        self.chars = mini_prompt.chars().chain(found.chars()).collect();
+        self.head = mini_prompt.chars().count() + state.pos;
+        self.check_scroll();
+
+        let chars: Vec<char> = found.chars().collect();
+        let start = std::cmp::min(state.pos, chars.len());
+        let end = std::cmp::min(start + state.query.chars().count(), chars.len());
+        let before: String = chars[..start].iter().collect();
+        let matched: String = chars[start..end].iter().collect();
+        let after: String = chars[end..].iter().collect();
+
+        self.goto(0);
+        self.write(termion::clear::AfterCursor.as_ref());
+        self.write(&mini_prompt);
+        self.write(&before.replace("\n", "\n\r"));
+        match state.failed {
+            true  => self.write(&matched.replace("\n", "\n\r")),
+            false => self.write(&format!("\x1b[01;7m{}\x1b[00m", matched.replace("\n", "\n\r"))),
+        }
+        self.write(&after.replace("\n", "\n\r"));
+        self.goto(self.head);
+        self.flush();
+    }
+}
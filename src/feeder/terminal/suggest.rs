@@ -0,0 +1,81 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::feeder::terminal::Terminal;
+use unicode_width::UnicodeWidthChar;
+
+impl Terminal {
+    fn find_suggestion(&self, core: &ShellCore) -> String {
+        let min = self.prompt.chars().count();
+        if self.head != self.chars.len() || self.head == min {
+            return String::new();
+        }
+
+        let input = self.get_string(min);
+        core.history.iter().skip(1)
+            .find(|h| h.len() > input.len() && h.starts_with(&input) && ! h.contains("↵ \0"))
+            .map(|h| h[input.len()..].to_string())
+            .unwrap_or_default()
+    }
+
+    /* shows the rest of the latest history entry starting with the input in dim color */
+    pub fn suggest(&mut self, core: &mut ShellCore) {
+        if ! core.shopts.query("autosuggest") {
+            return;
+        }
+
+        let suggestion = self.find_suggestion(core);
+        if suggestion.is_empty() && self.suggestion.is_empty() {
+            return;
+        }
+
+        self.erase_suggestion();
+        if ! suggestion.is_empty() {
+            /* the ghost text is cut at the right end so as not to wrap */
+            let room = Terminal::size().0.saturating_sub(self.head_to_cursor_pos(self.head, 0).0);
+            let mut width = 0;
+            let shown: String = suggestion.chars().take_while(|c| {
+                width += UnicodeWidthChar::width(*c).unwrap_or(0);
+                width <= room
+            }).collect();
+            self.write(&format!("\x1b[90m{}\x1b[00m", shown));
+            self.goto(self.head);
+        }
+        self.suggestion = suggestion;
+        self.flush();
+    }
+
+    pub fn erase_suggestion(&mut self) {
+        if self.suggestion.is_empty() {
+            return;
+        }
+        self.goto(self.chars.len());
+        self.write(termion::clear::UntilNewline.as_ref());
+        self.goto(self.head);
+        self.suggestion.clear();
+    }
+
+    /* takes the whole suggestion or its first word into the input */
+    pub fn accept_suggestion(&mut self, word: bool) -> bool {
+        if self.suggestion.is_empty() || self.head != self.chars.len() {
+            return false;
+        }
+
+        let suggestion = std::mem::take(&mut self.suggestion);
+        let accepted: String = match word {
+            false => suggestion,
+            true  => {
+                let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+                let lead = suggestion.chars().take_while(|c| ! is_word(c)).count();
+                let body = suggestion.chars().skip(lead).take_while(is_word).count();
+                suggestion.chars().take(lead + body).collect()
+            },
+        };
+
+        self.chars.extend(accepted.chars());
+        self.head = self.chars.len();
+        self.rewrite(true);
+        true
+    }
+}
@@ -0,0 +1,462 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::feeder::terminal::Terminal;
+use crate::feeder::terminal::edit::EditResult;
+use std::io::Stdin;
+use termion::event;
+use termion::input::Keys;
+
+#[derive(Default)]
+pub struct ViState {
+    pub command_mode: bool,
+    pub count: Option<usize>,
+    pub log: Vec<event::Key>,
+    pub last_change: Vec<event::Key>,
+    pub inserting: bool,
+    char_search: Option<(char, char)>,
+    search: Option<(String, bool)>,
+}
+
+/* the largest repeat count, the same as the numeric argument of readline */
+const MAX_COUNT: usize = 1000000;
+
+fn push_digit(count: usize, digit: usize) -> usize {
+    std::cmp::min(count.saturating_mul(10).saturating_add(digit), MAX_COUNT)
+}
+
+fn mul_count(count: usize, count2: usize) -> usize {
+    std::cmp::min(count.saturating_mul(count2), MAX_COUNT)
+}
+
+#[derive(PartialEq)]
+enum CharClass {
+    Blank,
+    Word,
+    Other,
+}
+
+fn class(c: char, big_word: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Blank
+    }else if big_word || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    }else{
+        CharClass::Other
+    }
+}
+
+impl Terminal {
+    fn class_at(&self, pos: usize, big_word: bool) -> CharClass {
+        class(self.chars[pos], big_word)
+    }
+
+    fn next_word(&self, pos: usize, big_word: bool) -> usize {
+        let len = self.chars.len();
+        let mut pos = pos;
+        if pos < len && self.class_at(pos, big_word) != CharClass::Blank {
+            let cls = self.class_at(pos, big_word);
+            while pos < len && self.class_at(pos, big_word) == cls {
+                pos += 1;
+            }
+        }
+        while pos < len && self.class_at(pos, big_word) == CharClass::Blank {
+            pos += 1;
+        }
+        pos
+    }
+
+    /* the end of the word at or after pos */
+    fn end_word(&self, pos: usize, big_word: bool) -> usize {
+        let len = self.chars.len();
+        let mut pos = pos;
+        while pos < len && self.class_at(pos, big_word) == CharClass::Blank {
+            pos += 1;
+        }
+        if pos >= len {
+            return len.saturating_sub(1).max(self.prompt_len());
+        }
+        let cls = self.class_at(pos, big_word);
+        while pos + 1 < len && self.class_at(pos + 1, big_word) == cls {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn prev_word(&self, pos: usize, big_word: bool) -> usize {
+        let min = self.prompt_len();
+        let mut pos = pos;
+        while pos > min && self.class_at(pos - 1, big_word) == CharClass::Blank {
+            pos -= 1;
+        }
+        if pos == min {
+            return min;
+        }
+        let cls = self.class_at(pos - 1, big_word);
+        while pos > min && self.class_at(pos - 1, big_word) == cls {
+            pos -= 1;
+        }
+        pos
+    }
+
+    fn first_print(&self) -> usize {
+        let mut pos = self.prompt_len();
+        while pos < self.chars.len() && self.chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn find_char(&self, pos: usize, command: char, target: char) -> Option<usize> {
+        let min = self.prompt_len();
+        match command {
+            'f' | 't' => {
+                let start = if command == 't' { pos + 2 } else { pos + 1 };
+                let found = (start..self.chars.len()).find(|i| self.chars[*i] == target)?;
+                Some(if command == 't' { found - 1 } else { found })
+            },
+            _ => {
+                let end = if command == 'T' { pos.saturating_sub(1) } else { pos };
+                let found = (min..end).rev().find(|i| self.chars[*i] == target)?;
+                Some(if command == 'T' { found + 1 } else { found })
+            },
+        }
+    }
+
+    fn char_search(&mut self, command: char, count: usize, keys: &mut Keys<Stdin>) -> Option<(usize, bool)> {
+        let (command, target) = match command {
+            ';' | ',' => {
+                let (c, t) = self.vi.char_search?;
+                let reversed = match c {
+                    'f' => 'F', 'F' => 'f', 't' => 'T', _ => 't',
+                };
+                (if command == ';' { c } else { reversed }, t)
+            },
+            _ => {
+                let target = match self.next_key(keys)? {
+                    event::Key::Char(c) => c,
+                    _ => return None,
+                };
+                self.vi.char_search = Some((command, target));
+                (command, target)
+            },
+        };
+
+        let mut pos = self.head;
+        for _ in 0..count {
+            pos = self.find_char(pos, command, target)?;
+        }
+        Some((pos, command == 'f' || command == 't'))
+    }
+
+    /* the destination of a motion and whether the character there is included */
+    fn motion(&mut self, key: event::Key, count: usize, keys: &mut Keys<Stdin>) -> Option<(usize, bool)> {
+        let (min, len) = (self.prompt_len(), self.chars.len());
+        let c = match key {
+            event::Key::Char(c) => c,
+            event::Key::Backspace | event::Key::Left => 'h',
+            event::Key::Right => 'l',
+            _ => return None,
+        };
+
+        let mut pos = self.head;
+        let inclusive = match c {
+            'h' => { pos = pos.saturating_sub(count).max(min); false },
+            'l' | ' ' => { pos = std::cmp::min(pos + count, len); false },
+            'w' | 'W' => {
+                (0..count).for_each(|_| pos = self.next_word(pos, c == 'W'));
+                false
+            },
+            'b' | 'B' => {
+                (0..count).for_each(|_| pos = self.prev_word(pos, c == 'B'));
+                false
+            },
+            'e' | 'E' => {
+                (0..count).for_each(|_| pos = self.end_word(pos + 1, c == 'E'));
+                true
+            },
+            '0' => { pos = min; false },
+            '^' => { pos = self.first_print(); false },
+            '$' => { pos = len; false },
+            'f' | 'F' | 't' | 'T' | ';' | ',' => return self.char_search(c, count, keys),
+            _ => return None,
+        };
+        Some((pos, inclusive))
+    }
+
+    fn start_insertion(&mut self, pos: usize) {
+        self.vi.command_mode = false;
+        self.vi.inserting = true;
+        self.move_to(pos);
+    }
+
+    fn read_count(&mut self, first: event::Key, keys: &mut Keys<Stdin>) -> (usize, Option<event::Key>) {
+        let mut count = 0;
+        let mut key = Some(first);
+        while let Some(event::Key::Char(c)) = key {
+            if ! c.is_ascii_digit() || (c == '0' && count == 0) {
+                break;
+            }
+            count = push_digit(count, c.to_digit(10).unwrap() as usize);
+            key = self.next_key(keys);
+        }
+        (std::cmp::max(count, 1), key)
+    }
+
+    fn operator(&mut self, core: &mut ShellCore, op: char, keys: &mut Keys<Stdin>) {
+        let count = self.vi.count.take().unwrap_or(1);
+        let (min, len) = (self.prompt_len(), self.chars.len());
+
+        let (from, to) = match op.is_uppercase() {
+            true  => (self.head, len),
+            false => {
+                let key = match self.next_key(keys) {
+                    Some(k) => k,
+                    None => return,
+                };
+                let (count2, key) = self.read_count(key, keys);
+                let key = match key {
+                    Some(k) => k,
+                    None => return,
+                };
+
+                /* cw and cW work like ce and cE even on the last character of a word */
+                let word_change = op == 'c' && self.head < len
+                                  && ! self.chars[self.head].is_whitespace();
+                if let (true, event::Key::Char(c @ ('w' | 'W'))) = (word_change, key) {
+                    let mut pos = self.end_word(self.head, c == 'W');
+                    (1..mul_count(count, count2)).for_each(|_| pos = self.end_word(pos + 1, c == 'W'));
+                    self.kill(core, self.head, std::cmp::min(pos + 1, len));
+                    self.start_insertion(self.head);
+                    return;
+                }
+
+                if key == event::Key::Char(op) {
+                    (min, len)
+                }else{
+                    match self.motion(key, mul_count(count, count2), keys) {
+                        Some((pos, incl)) => {
+                            let end = std::cmp::max(self.head, pos);
+                            let end = if incl { std::cmp::min(end + 1, len) } else { end };
+                            (std::cmp::min(self.head, pos), end)
+                        },
+                        None => {
+                            self.cloop();
+                            return;
+                        },
+                    }
+                }
+            },
+        };
+
+        match op.to_ascii_lowercase() {
+            'y' => {
+                core.kill_buffer = self.chars[from..to].iter().collect();
+                self.move_to(from);
+            },
+            'd' => self.kill(core, from, to),
+            _ => {
+                self.kill(core, from, to);
+                self.start_insertion(from);
+            },
+        }
+    }
+
+    fn put(&mut self, core: &mut ShellCore, after: bool) {
+        let count = self.vi.count.take().unwrap_or(1);
+        if core.kill_buffer.is_empty() {
+            return;
+        }
+        if after && self.head < self.chars.len() {
+            self.head += 1;
+        }
+        for _ in 0..count {
+            core.kill_buffer.clone().chars().for_each(|c| self.insert(c));
+        }
+        self.move_to(self.head - 1);
+    }
+
+    fn change_chars(&mut self, f: fn(char) -> String, count: usize) {
+        let end = std::cmp::min(self.head + count, self.chars.len());
+        let changed: Vec<char> = self.chars[self.head..end].iter()
+                                 .flat_map(|c| f(*c).chars().collect::<Vec<char>>())
+                                 .collect();
+        self.chars.splice(self.head..end, changed);
+        self.head = end;
+        self.rewrite(true);
+    }
+
+    fn read_search_pattern(&mut self, mark: &str, keys: &mut Keys<Stdin>) -> Option<String> {
+        let prompt = self.prompt.clone();
+        let width_map = self.prompt_width_map.clone();
+        let chars = self.chars.clone();
+        let head = self.head;
+
+        self.prompt = mark.to_string();
+        self.prompt_width_map = vec![1];
+        self.chars = mark.chars().collect();
+        self.head = 1;
+        self.rewrite(true);
+
+        let mut pattern = None;
+        while let Some(key) = self.next_key(keys) {
+            match key {
+                event::Key::Char('\n') => {
+                    pattern = Some(self.get_string(1));
+                    break;
+                },
+                event::Key::Backspace if self.chars.len() == 1 => break,
+                event::Key::Backspace => self.backspace(),
+                event::Key::Char(c) => self.insert(c),
+                event::Key::Esc | event::Key::Ctrl(_) => break,
+                _ => {},
+            }
+        }
+
+        self.prompt = prompt;
+        self.prompt_width_map = width_map;
+        self.chars = chars;
+        self.head = head;
+        self.rewrite(true);
+        pattern
+    }
+
+    fn search_again(&mut self, core: &mut ShellCore, reverse: bool) {
+        let (pattern, forward) = match &self.vi.search {
+            Some(s) => s.clone(),
+            None => {
+                self.cloop();
+                return;
+            },
+        };
+
+        let older = forward == reverse;
+        let matches = |h: &str| match pattern.strip_prefix('^') {
+            Some(p) => h.starts_with(p),
+            None    => h.contains(&pattern),
+        };
+
+        let len = core.history.len() as i32;
+        let mut ptr = self.hist_ptr as i32;
+        loop {
+            ptr += if older { 1 } else { -1 };
+            if ptr < 0 || ptr >= len {
+                self.cloop();
+                return;
+            }
+            if matches(&core.history[ptr as usize].replace("↵ \0", "\n")) {
+                break;
+            }
+        }
+
+        self.call_history(ptr - self.hist_ptr as i32, core);
+        self.move_to(self.prompt_len());
+    }
+
+    fn search(&mut self, core: &mut ShellCore, typed: &str, keys: &mut Keys<Stdin>) {
+        let pattern = match self.read_search_pattern(typed, keys) {
+            Some(p) => p,
+            None => return,
+        };
+
+        if ! pattern.is_empty() {
+            self.vi.search = Some((pattern, typed == "?"));
+        }else if let Some((p, _)) = self.vi.search.take() {
+            self.vi.search = Some((p, typed == "?"));
+        }
+        self.search_again(core, false);
+    }
+
+    pub fn vi_function(&mut self, core: &mut ShellCore, name: &str, typed: &str,
+                       keys: &mut Keys<Stdin>) -> EditResult {
+        let key = typed.chars().next().unwrap_or(' ');
+        let (min, len) = (self.prompt_len(), self.chars.len());
+
+        match name {
+            "vi-arg-digit" => {
+                let digit = key.to_digit(10).unwrap_or(0) as usize;
+                self.vi.count = Some(push_digit(self.vi.count.unwrap_or(0), digit));
+                return EditResult::Continue;
+            },
+            "vi-change-to" | "vi-delete-to" | "vi-yank-to" => {
+                let op = match (name, key.is_uppercase()) {
+                    ("vi-change-to", u) => if u { 'C' } else { 'c' },
+                    ("vi-delete-to", u) => if u { 'D' } else { 'd' },
+                    (_, u) => if u { 'Y' } else { 'y' },
+                };
+                self.operator(core, op, keys);
+            },
+            "vi-movement-mode" => {
+                if ! self.vi.command_mode {
+                    self.vi.command_mode = true;
+                    if self.vi.inserting {
+                        self.vi.last_change = self.vi.log.clone();
+                        self.vi.inserting = false;
+                    }
+                    if self.head > min {
+                        self.move_to(self.head - 1);
+                    }
+                }
+            },
+            "vi-insertion-mode" => self.start_insertion(self.head),
+            "vi-append-mode" => self.start_insertion(std::cmp::min(self.head + 1, len)),
+            "vi-append-eol" => self.start_insertion(len),
+            "vi-insert-beg" => self.start_insertion(self.first_print()),
+            "vi-subst" => {
+                let count = self.vi.count.take().unwrap_or(1);
+                match key {
+                    'S' => self.kill(core, min, len),
+                    _   => self.kill(core, self.head, std::cmp::min(self.head + count, len)),
+                }
+                self.start_insertion(self.head);
+            },
+            "vi-first-print" => self.move_to(self.first_print()),
+            "vi-next-word" | "vi-prev-word" | "vi-end-word" | "vi-char-search" => {
+                let count = self.vi.count.take().unwrap_or(1);
+                match self.motion(event::Key::Char(key), count, keys) {
+                    Some((pos, _)) => self.move_to(pos),
+                    None => self.cloop(),
+                }
+            },
+            "vi-delete" | "vi-rubout" => {
+                let count = self.vi.count.take().unwrap_or(1);
+                match name {
+                    "vi-delete" => self.kill(core, self.head, std::cmp::min(self.head + count, len)),
+                    _ => self.kill(core, self.head.saturating_sub(count).max(min), self.head),
+                }
+            },
+            "vi-put" => self.put(core, key == 'p'),
+            "vi-undo" => self.undo(),
+            "vi-redo" => {
+                self.vi.count = None;
+                for k in self.vi.last_change.clone().into_iter().rev() {
+                    self.pending.push_front(k);
+                }
+            },
+            "vi-change-char" => {
+                let count = self.vi.count.take().unwrap_or(1);
+                if let Some(event::Key::Char(c)) = self.next_key(keys) {
+                    if self.head + count <= len {
+                        let head = self.head;
+                        self.chars.splice(head..head+count, std::iter::repeat_n(c, count));
+                        self.head = head + count - 1;
+                        self.rewrite(true);
+                    }
+                }
+            },
+            "vi-change-case" => {
+                let count = self.vi.count.take().unwrap_or(1);
+                self.change_chars(|c| match c.is_uppercase() {
+                    true  => c.to_lowercase().to_string(),
+                    false => c.to_uppercase().to_string(),
+                }, count);
+            },
+            "vi-search" => self.search(core, typed, keys),
+            "vi-search-again" => self.search_again(core, key == 'N'),
+            _ => self.cloop(),
+        }
+        self.vi.count = None;
+        EditResult::Continue
+    }
+}
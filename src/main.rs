@@ -94,18 +94,6 @@ fn main() {
     main_loop(&mut core);
 }
 
-fn set_history(core: &mut ShellCore, s: &str) {
-    if core.read_stdin || core.history.is_empty() {
-        return;
-    }
-
-    core.history[0] = s.trim_end().replace("\n", "↵ \0").to_string();
-    if core.history[0].is_empty()
-    || (core.history.len() > 1 && core.history[0] == core.history[1]) {
-        core.history.remove(0);
-    }
-}
-
 fn show_message() {
     const V: &'static str = env!("CARGO_PKG_VERSION");
     const P: &'static str = env!("CARGO_BUILD_PROFILE");
@@ -116,24 +104,37 @@ fn main_loop(core: &mut ShellCore) {
     let mut feeder = Feeder::new("");
 
     if core.script_name != "-" {
-        core.db.flags.retain(|f| f != 'i');
+        core.db.flags.retain(|f| f != 'i' && f != 'H');
         feeder.set_file(&core.script_name);
+        let args = core.db.get_position_params();
+        let _ = core.db.set_array("BASH_SOURCE", vec![core.script_name.clone()], None);
+        let _ = core.db.set_array("BASH_LINENO", vec!["0".to_string()], None);
+        let _ = core.db.set_array("BASH_ARGV", args.iter().rev().cloned().collect(), None);
+        let _ = core.db.set_array("BASH_ARGC", vec![args.len().to_string()], None);
     }
 
     if core.db.flags.contains('i') {
         show_message();
     }
+    core.load_history_file();
+    core.load_inputrc();
 
+    let mut eof_count = 0;
+    let mut syntax_error = false;
     loop {
         core.jobtable_check_status();
         core.jobtable_print_status_change();
 
         match feeder.feed_line(core) {
-            Ok(()) => {}, 
+            Ok(()) => eof_count = 0, 
             Err(InputError::Interrupt) => {
                 signal::input_interrupt_check(&mut feeder, core);
                 continue;
             },
+            Err(InputError::Eof) if ignore_eof(core, &mut eof_count) => {
+                eprintln!("Use \"exit\" to leave the shell.");
+                continue;
+            },
             _ => break,
         }
 
@@ -141,25 +142,50 @@ fn main_loop(core: &mut ShellCore) {
         core.sigint.store(false, Relaxed);
         match Script::parse(&mut feeder, core, false){
             Ok(Some(mut s)) => {
+                if core.db.flags.contains('i') {
+                    core.print_ps0();
+                    core.command_number += 1;
+                }
+                let onecmd = core.db.flags.contains('t');
                 let _ = s.exec(core);
-                set_history(core, &s.get_text());
+                core.set_history(&s.get_text());
+                if onecmd {
+                    break;
+                }
             },
             Err(e) => {
                 e.print(core);
+                syntax_error = true;
                 feeder.consume(feeder.len());
                 feeder.nest = vec![("".to_string(), vec![])];
             },
             _ => {
+                core.set_history("");
                 feeder.consume(feeder.len());
                 feeder.nest = vec![("".to_string(), vec![])];
             },
         }
         core.sigint.store(false, Relaxed);
     }
+    if core.noexec() {
+        core.db.exit_status = if syntax_error {2} else {0};
+    }
     core.write_history_to_file();
     exit::normal(core);
 }
 
+/* set -o ignoreeof: IGNOREEOF gives the number of consecutive EOFs to be ignored */
+fn ignore_eof(core: &mut ShellCore, eof_count: &mut usize) -> bool {
+    if ! core.db.flags.contains('i') || ! core.options.query("ignoreeof") {
+        return false;
+    }
+
+    let limit = core.db.get_param("IGNOREEOF").unwrap_or_default()
+                .parse::<usize>().unwrap_or(10);
+    *eof_count += 1;
+    *eof_count <= limit
+}
+
 fn run_and_exit_c_option(args: &Vec<String>, c_parts: &Vec<String>) {
     if c_parts.len() < 2 {
         println!("{}: -c: option requires an argument", &args[0]);
@@ -182,7 +208,7 @@ fn run_and_exit_c_option(args: &Vec<String>, c_parts: &Vec<String>) {
         panic!("");
     }
     signal::run_signal_check(&mut core);
-    core.db.flags.retain(|f| f != 'i');
+    core.db.flags.retain(|f| f != 'i' && f != 'H');
 
     core.db.flags += "c";
     if core.db.flags.contains('v') {
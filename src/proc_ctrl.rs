@@ -24,7 +24,6 @@ pub fn wait_pipeline(core: &mut ShellCore, pids: Vec<Option<Pid>>,
         if exclamation {
             core.flip_exit_status();
         }
-        exit::check_e_option(core);
         return vec![];
     }
 
@@ -55,8 +54,6 @@ pub fn wait_pipeline(core: &mut ShellCore, pids: Vec<Option<Pid>>,
         core.flip_exit_status();
     }
 
-    exit::check_e_option(core);
-
     ans
 }
 
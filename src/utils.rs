@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 pub mod directory;
+pub mod ere;
 pub mod clock;
 pub mod exit;
 pub mod file;
@@ -12,6 +13,12 @@ pub mod random;
 
 use crate::{Feeder, ShellCore};
 
+/* the reserved words of bash in the order of compgen -k */
+pub const KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "case", "esac", "for", "select", "while",
+    "until", "do", "done", "in", "function", "time", "{", "}", "!", "[[", "]]", "coproc",
+];
+
 pub fn reserved(w: &str) -> bool {
     match w {
         "[[" | "]]" | "{" | "}" | "while" | "for" | "do" | "done" | "if" | "then" | "elif" | "else" | "fi" | "case" => true,
@@ -19,55 +26,6 @@ pub fn reserved(w: &str) -> bool {
     }
 }
 
-pub fn split_words(s: &str) -> Vec<String> {
-    let mut ans = vec![];
-
-    let mut in_quote = false;
-    let mut escaped = false;
-    let mut quote = ' ';
-
-    let mut tmp = String::new();
-    for c in s.chars() {
-        if escaped || c == '\\' {
-            escaped = ! escaped;
-            tmp.push(c);
-            continue;
-        }
-
-        if c == '\'' || c == '"' {
-            if c == quote {
-                in_quote = ! in_quote;
-                quote = ' ';
-            }else if quote == ' ' {
-                in_quote = ! in_quote;
-                quote = c;
-            }
-            tmp.push(c);
-            continue;
-        }
-
-        if in_quote {
-            tmp.push(c);
-            continue;
-        }
-
-        if ! in_quote && ( c == ' ' || c == '\t') {
-            if tmp.len() != 0 {
-                ans.push(tmp.clone());
-                tmp.clear();
-            }
-        }else{
-            tmp.push(c);
-        }
-    }
-
-    if tmp.len() != 0 {
-        ans.push(tmp);
-    }
-
-    ans
-}
-
 pub fn is_wsl() -> bool {
     if let Ok(info) = nix::sys::utsname::uname() {
         let release = info.release().to_string_lossy().to_string();
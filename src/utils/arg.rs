@@ -1,21 +1,6 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-pub fn consume_with_next_arg(prev_opt: &str, args: &mut Vec<String>) -> Option<String> {
-    match args.iter().position(|a| a == prev_opt) {
-        Some(pos) => {
-            match pos+1 >= args.len() {
-                true  => None,
-                false => {
-                    args.remove(pos);
-                    Some(args.remove(pos))
-                },
-            }
-        },
-        None => None,
-    }
-}
-
 pub fn consume_with_subsequents(prev_opt: &str, args: &mut Vec<String>) -> Vec<String> {
     match args.iter().position(|a| a == prev_opt) {
         Some(pos) => {
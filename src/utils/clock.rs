@@ -1,8 +1,10 @@
 //SPDX-FileCopyrightText: 2024 @caro@mi.shellgei.org
 //SPDX-License-Identifier: BSD-3-Clause
 
+use std::ffi::CString;
 use std::str::FromStr;
 use ::time::Duration;
+use nix::libc;
 use nix::time;
 use nix::time::ClockId;
 
@@ -32,14 +34,42 @@ pub fn get_seconds(v: &mut Vec<String>) -> String {
     ans
 }
 
+pub fn epoch_seconds() -> i64 {
+    time::clock_gettime(ClockId::CLOCK_REALTIME).unwrap().tv_sec()
+}
+
+/* libc binds tzset only for Windows, so it is declared here
+ * (localtime_r does not read a changed TZ by itself) */
+extern "C" {
+    fn tzset();
+}
+
+pub fn strftime(format: &str, epoch: i64) -> String {
+    let fmt = match CString::new(format) {
+        Ok(f) => f,
+        Err(_) => return String::new(),
+    };
+    let t = epoch as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0u8; 1024];
+
+    let len = unsafe {
+        tzset();
+        if libc::localtime_r(&t, &mut tm).is_null() {
+            return String::new();
+        }
+        libc::strftime(buf.as_mut_ptr() as *mut libc::c_char, buf.len(), fmt.as_ptr(), &tm)
+    };
+
+    String::from_utf8_lossy(&buf[..len]).to_string()
+}
+
 pub fn get_epochseconds(_: &mut Vec<String>) -> String {
     let real = time::clock_gettime(ClockId::CLOCK_REALTIME).unwrap();
-    let epoch_seconds = real.tv_sec().to_string();
-    epoch_seconds
+    real.tv_sec().to_string()
 }
 
 pub fn get_epochrealtime(_: &mut Vec<String>) -> String {
     let real = time::clock_gettime(ClockId::CLOCK_REALTIME).unwrap();
-    let epoch_realtime = format!("{}.{:06}", real.tv_sec(), real.tv_nsec() / 1000).to_string();
-    epoch_realtime
+    format!("{}.{:06}", real.tv_sec(), real.tv_nsec() / 1000)
 }
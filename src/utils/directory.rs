@@ -1,6 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
+use crate::core::options::Options;
 use std::fs::DirEntry;
 use std::path::Path;
 use super::glob;
@@ -20,7 +21,7 @@ pub fn files(dir: &str) -> Vec<String> {
     entries.map(|e| f(e.unwrap()) ).collect()
 }
 
-pub fn glob(dir: &str, pattern: &str, extglob: bool) -> Vec<String> {
+pub fn glob(dir: &str, pattern: &str, shopts: &Options) -> Vec<String> {
     let make_path = |f: &str| dir.to_owned() + f + "/";
 
     if ["", ".", ".."].contains(&pattern) {
@@ -31,9 +32,48 @@ pub fn glob(dir: &str, pattern: &str, extglob: bool) -> Vec<String> {
         }
     }
 
-    let pat = glob::parse(pattern, extglob);
+    let nocase = shopts.query("nocaseglob");
+    let pat = match nocase {
+        true  => glob::parse_nocase(pattern, shopts.query("extglob")),
+        false => glob::parse(pattern, shopts.query("extglob")),
+    };
+    let dotglob = shopts.query("dotglob") || pattern.starts_with(".");
+
     files(dir).iter()
-        .filter(|f| !f.starts_with(".") || pattern.starts_with(".") )
-        .filter(|f| glob::compare(f, &pat) )
+        .filter(|f| !f.starts_with(".") || dotglob )
+        .filter(|f| match nocase {
+            true  => glob::compare(&glob::fold(f), &pat),
+            false => glob::compare(f, &pat),
+        })
         .map(|f| make_path(&f) ).collect()
 }
+
+pub fn is_dir(dir: &str) -> bool {
+    let dir = if dir.is_empty() {"."}else{dir};
+    Path::new(dir).is_dir()
+}
+
+/* all the paths under the directory with trailing slashes; symbolic links
+ * to directories are not followed so as not to fall into loops */
+pub fn descendants(dir: &str, dotglob: bool) -> Vec<String> {
+    let mut ans = vec![];
+    for f in files(dir) {
+        if f.starts_with(".") && ! dotglob {
+            continue;
+        }
+
+        let path = dir.to_owned() + &f + "/";
+        ans.push(path.clone());
+        if is_real_dir(&path) {
+            ans.append(&mut descendants(&path, dotglob));
+        }
+    }
+    ans
+}
+
+pub fn is_real_dir(path: &str) -> bool {
+    match Path::new(path.trim_end_matches('/')).symlink_metadata() {
+        Ok(m) => m.is_dir(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,159 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use regex::{Regex, RegexBuilder};
+
+/* never matches; used for $ when the text is cut before its end */
+const NO_END: &str = r"[^\s\S]";
+
+fn escape(c: char) -> String {
+    regex::escape(&c.to_string())
+}
+
+/* a bracket expression from pos; [, &, ~ and \ are escaped
+ * because they have other meanings in the regex crate */
+fn bracket(chars: &[char], pos: usize) -> Option<(String, usize)> {
+    let mut i = pos + 1;
+    let mut ans = "[".to_string();
+    if chars.get(i) == Some(&'^') {
+        ans.push('^');
+        i += 1;
+    }
+    if chars.get(i) == Some(&']') {
+        ans += r"\]";
+        i += 1;
+    }
+
+    let first = i;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == ']' {
+            return Some((ans + "]", i + 1));
+        }
+
+        if c == '[' && i + 1 < chars.len() && ":=.".contains(chars[i+1]) {
+            let delim = chars[i+1];
+            let end = (i+2..chars.len().saturating_sub(1))
+                      .find(|j| chars[*j] == delim && chars[j+1] == ']')?;
+            let inner: String = chars[i+2..end].iter().collect();
+            match delim {
+                ':' => ans += &format!("[:{}:]", inner),
+                _   => ans += &inner.chars().map(escape).collect::<String>(),
+            }
+            i = end + 2;
+            continue;
+        }
+
+        match c {
+            '-' if i == first || chars.get(i+1) == Some(&']') || ans.ends_with('-')
+                => ans += r"\-",
+            '\\' | '[' | '&' | '~' | '^' => ans += &escape(c),
+            _ => ans.push(c),
+        }
+        i += 1;
+    }
+    None
+}
+
+/* the length of an interval such as {2}, {2,} and {2,3} from pos */
+fn interval_len(chars: &[char], pos: usize) -> Option<usize> {
+    let end = (pos..chars.len()).find(|i| chars[*i] == '}')?;
+    let inner: String = chars[pos+1..end].iter().collect();
+    let mut nums = inner.splitn(2, ',');
+    let min = nums.next().unwrap_or("");
+    let max = nums.next().unwrap_or("");
+    let is_num = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    match ! min.is_empty() && is_num(min) && is_num(max) {
+        true  => Some(end - pos + 1),
+        false => None,
+    }
+}
+
+/* translates a POSIX extended regular expression into the syntax of the regex crate */
+fn translate(pattern: &str, end: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut ans = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                let c = chars[i+1];
+                match c {
+                    '<' | '>' => ans += r"\b",
+                    'b' | 'B' | 'w' | 'W' | 's' | 'S' => ans += &format!("\\{}", c),
+                    _ => ans += &escape(c),
+                }
+                i += 2;
+            },
+            '[' => match bracket(&chars, i) {
+                Some((b, next)) => {
+                    ans += &b;
+                    i = next;
+                },
+                None => {
+                    ans += r"\[";
+                    i += 1;
+                },
+            },
+            '{' => match interval_len(&chars, i) {
+                Some(len) if ! ans.is_empty() => {
+                    ans += &chars[i..i+len].iter().collect::<String>();
+                    i += len;
+                },
+                _ => {
+                    ans += r"\{";
+                    i += 1;
+                },
+            },
+            '}' | '\\' => {
+                ans += &escape(chars[i]);
+                i += 1;
+            },
+            '$' => {
+                ans += end;
+                i += 1;
+            },
+            c => {
+                ans.push(c);
+                i += 1;
+            },
+        }
+    }
+    ans
+}
+
+fn build(pattern: &str, nocase: bool) -> Result<Regex, String> {
+    RegexBuilder::new(pattern).case_insensitive(nocase)
+        .build().map_err(|e| e.to_string())
+}
+
+/* returns the leftmost-longest match and its groups as POSIX regexec does;
+ * a group that takes no part in the match becomes an empty string */
+pub fn captures(text: &str, pattern: &str, nocase: bool) -> Result<Option<Vec<String>>, String> {
+    let re = build(&translate(pattern, "$"), nocase)?;
+    let start = match re.find(text) {
+        Some(m) => m.start(),
+        None => return Ok(None),
+    };
+
+    let at_end = build(&format!("(?:{})\\z", translate(pattern, "$")), nocase)?;
+    let cut = build(&format!("(?:{})\\z", translate(pattern, NO_END)), nocase)?;
+
+    let mut ends: Vec<usize> = text.char_indices().map(|(i, _)| i)
+                               .filter(|i| *i >= start).collect();
+    ends.push(text.len());
+    for end in ends.into_iter().rev() {
+        let target = if end == text.len() { &at_end } else { &cut };
+        if let Some(caps) = target.captures_at(&text[..end], start) {
+            if caps.get(0).map(|m| m.start()) != Some(start) {
+                continue;
+            }
+            let groups = caps.iter()
+                         .map(|g| g.map_or("", |m| m.as_str()).to_string())
+                         .collect();
+            return Ok(Some(groups));
+        }
+    }
+    Ok(None)
+}
@@ -3,6 +3,7 @@
 
 use crate::ShellCore;
 use crate::error;
+use crate::error::exec::ExecError;
 use std::process;
 
 pub fn normal(core: &mut ShellCore) -> ! {
@@ -34,9 +35,28 @@ pub fn internal(s: &str) -> ! {
 }
 
 pub fn check_e_option(core: &mut ShellCore) {
-    if core.db.exit_status != 0 
+    let failed = core.db.exit_status != 0;
+    if failed
     && core.db.flags.contains("e") 
-    && ! core.suspend_e_option {
+    && ! core.suspend_e_option
+    && ! core.e_failure_suspended {
         normal(core);
     }
+    core.e_failure_suspended = failed && (core.suspend_e_option || core.e_failure_suspended);
+}
+
+/* an error of set -u, which terminates a non-interactive shell */
+pub fn unbound_variable(name: &str, core: &mut ShellCore) -> ExecError {
+    let name = match name.parse::<usize>() {
+        Ok(_) => format!("${}", name),
+        _     => name.to_string(),
+    };
+    let e = ExecError::UnboundVariable(name);
+    core.db.exit_status = 1;
+    if ! core.db.flags.contains('i') {
+        e.print(core);
+        core.db.exit_status = 127;
+        normal(core);
+    }
+    e
 }
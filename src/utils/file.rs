@@ -3,9 +3,15 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::ShellCore;
+use crate::core::compspec::single_quote;
 use crate::utils::file_check;
-use std::env;
+use rand_chacha::ChaCha20Rng;
+use rand::{RngCore, SeedableRng};
+use std::{env, fs, io};
 use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf, Component};
 
 pub fn oss_to_name(oss: &OsString) -> String {
@@ -74,3 +80,47 @@ pub fn make_canonical_path(core: &mut ShellCore, path_str: &str) -> PathBuf {
     canonical
 }
 
+
+/* creates a file only the user can access in $TMPDIR; create_new fails on
+ * an existing path, so a file or symbolic link planted there is never opened */
+pub fn create_temp(core: &mut ShellCore, prefix: &str) -> Result<(File, String), io::Error> {
+    let dir = match core.db.get_param("TMPDIR").unwrap_or_default() {
+        d if d.is_empty() => "/tmp".to_string(),
+        d => d,
+    };
+
+    loop {
+        let path = format!("{}/{}.{}.{:08x}", dir.trim_end_matches('/'), prefix,
+                           std::process::id(), ChaCha20Rng::from_entropy().next_u32());
+        match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+            Ok(f) => return Ok((f, path)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/* the value of the first nonempty variable in names, or vi */
+pub fn editor(core: &mut ShellCore, names: &[&str]) -> String {
+    names.iter().map(|n| core.db.get_param(n).unwrap_or_default())
+         .find(|e| ! e.is_empty())
+         .unwrap_or("vi".to_string())
+}
+
+/* lets the user edit text with the editor; run executes the command line given to it
+ * and returns its exit status, and the edited text is returned with the status */
+pub fn edit_text(core: &mut ShellCore, editor: &str, text: &str,
+                 run: impl FnOnce(&mut ShellCore, &str) -> i32) -> Result<(String, i32), io::Error> {
+    let (mut file, path) = create_temp(core, "sush-edit")?;
+    let written = file.write_all(text.as_bytes());
+    drop(file);
+    if let Err(e) = written {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+
+    let status = run(core, &format!("{} {}", editor, single_quote(&path)));
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    Ok((edited?, status))
+}
@@ -1,6 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
+mod bracket;
 mod comparator;
 mod extglob;
 mod parser;
@@ -9,15 +10,31 @@ mod parser;
 pub enum GlobElem {
     Normal(String),
     Symbol(char),
-    OneOf(bool, Vec<char>),
+    OneOf(bool, Vec<BracketElem>),
     ExtGlob(char, Vec<String>),
 }
 
+/* a member of a bracket expression */
+#[derive(Debug)]
+pub enum BracketElem {
+    Char(char),
+    Range(char, char),
+    Class(String),
+    Equiv(char),
+    AnyCase(Vec<BracketElem>),
+}
+
 pub fn parse_and_compare(word: &String, pattern: &str, extglob: bool) -> bool {
     let pat = parser::parse(pattern, extglob);
     compare(word, &pat)
 }
 
+/* compares ignoring the case of letters for nocasematch */
+pub fn parse_and_compare_nocase(word: &String, pattern: &str, extglob: bool) -> bool {
+    let pat = parse_nocase(pattern, extglob);
+    compare(&fold(word), &pat)
+}
+
 pub fn compare(word: &String, pattern: &Vec<GlobElem>) -> bool {
     comparator::shave_word(word, pattern).iter().any(|c| c == "")
 }
@@ -35,3 +52,35 @@ pub fn shortest_match_length(word: &String, pattern: &Vec<GlobElem>) -> usize {
 pub fn parse(pattern: &str, extglob: bool) -> Vec<GlobElem> {
     parser::parse(pattern, extglob)
 }
+
+/* the pattern for a word folded with the function fold */
+pub fn parse_nocase(pattern: &str, extglob: bool) -> Vec<GlobElem> {
+    parser::parse(pattern, extglob).into_iter().map(|e| match e {
+        GlobElem::Normal(s) => GlobElem::Normal(fold(&s)),
+        GlobElem::OneOf(not, elems) => GlobElem::OneOf(not, elems.into_iter().map(any_case).collect()),
+        GlobElem::ExtGlob(prefix, ps) => GlobElem::ExtGlob(prefix, ps.iter().map(|p| fold(p)).collect()),
+        e => e,
+    }).collect()
+}
+
+/* characters and ranges match both cases while classes keep their meaning */
+fn any_case(elem: BracketElem) -> BracketElem {
+    match elem {
+        BracketElem::Char(_) | BracketElem::Range(_, _) => BracketElem::AnyCase(vec![elem]),
+        e => e,
+    }
+}
+
+/* Unicode-aware case folding which keeps the number of characters */
+pub fn fold(s: &str) -> String {
+    s.chars().map(fold_char).collect()
+}
+
+fn fold_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some('ς'), None) => 'σ',
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
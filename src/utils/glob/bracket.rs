@@ -0,0 +1,116 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use super::{BracketElem, GlobElem};
+
+impl BracketElem {
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            BracketElem::Char(x) => *x == c,
+            BracketElem::Range(from, to) => *from <= c && c <= *to,
+            BracketElem::Class(name) => class_matches(name, c),
+            BracketElem::Equiv(x) => base_letter(*x) == base_letter(c),
+            BracketElem::AnyCase(elems) => {
+                let mut variants = c.to_uppercase().chain(c.to_lowercase()).chain([c]);
+                variants.any(|v| elems.iter().any(|e| e.matches(v)))
+            },
+        }
+    }
+}
+
+fn class_matches(name: &str, c: char) -> bool {
+    match name {
+        "alnum"  => c.is_alphanumeric(),
+        "alpha"  => c.is_alphabetic(),
+        "ascii"  => c.is_ascii(),
+        "blank"  => c == ' ' || c == '\t',
+        "cntrl"  => c.is_control(),
+        "digit"  => c.is_ascii_digit(),
+        "graph"  => ! c.is_control() && ! c.is_whitespace(),
+        "lower"  => c.is_lowercase(),
+        "print"  => ! c.is_control(),
+        "punct"  => c.is_ascii_punctuation(),
+        "space"  => c.is_whitespace(),
+        "upper"  => c.is_uppercase(),
+        "word"   => c.is_alphanumeric() || c == '_',
+        "xdigit" => c.is_ascii_hexdigit(),
+        _ => false,
+    }
+}
+
+/* the letter without a diacritical mark for equivalence classes */
+fn base_letter(c: char) -> char {
+    match c {
+        'À'..='Å' => 'A', 'Ç' => 'C', 'È'..='Ë' => 'E', 'Ì'..='Ï' => 'I',
+        'Ñ' => 'N', 'Ò'..='Ö' | 'Ø' => 'O', 'Ù'..='Ü' => 'U', 'Ý' => 'Y',
+        'à'..='å' => 'a', 'ç' => 'c', 'è'..='ë' => 'e', 'ì'..='ï' => 'i',
+        'ñ' => 'n', 'ò'..='ö' | 'ø' => 'o', 'ù'..='ü' => 'u', 'ý' | 'ÿ' => 'y',
+        _ => c,
+    }
+}
+
+/* a member such as a, \], [:alpha:], [=e=] and [.-.] from pos */
+fn member(chars: &[char], pos: usize) -> Option<(BracketElem, usize)> {
+    let c = *chars.get(pos)?;
+    if c == '\\' {
+        return Some((BracketElem::Char(*chars.get(pos+1)?), pos + 2));
+    }
+
+    let delim = match chars.get(pos+1) {
+        Some(d) if c == '[' && ":=.".contains(*d) => *d,
+        _ => return Some((BracketElem::Char(c), pos + 1)),
+    };
+    let end = match (pos+2..chars.len().saturating_sub(1))
+                    .find(|i| chars[*i] == delim && chars[i+1] == ']') {
+        Some(e) => e,
+        None => return Some((BracketElem::Char(c), pos + 1)),
+    };
+
+    let inner: String = chars[pos+2..end].iter().collect();
+    let elem = match (delim, inner.chars().count()) {
+        (':', _) => BracketElem::Class(inner),
+        ('=', 1) => BracketElem::Equiv(inner.chars().next().unwrap()),
+        (_, 1)   => BracketElem::Char(inner.chars().next().unwrap()),
+        _        => BracketElem::Class(String::new()), //matches nothing
+    };
+    Some((elem, end + 2))
+}
+
+pub fn scan(pattern: &str) -> (usize, Option<GlobElem>) {
+    let chars: Vec<char> = pattern.chars().collect();
+    if chars.first() != Some(&'[') {
+        return (0, None);
+    }
+
+    let not = matches!(chars.get(1), Some('!') | Some('^'));
+    let mut pos = if not {2} else {1};
+    let mut elems = vec![];
+
+    loop {
+        match chars.get(pos) {
+            None => return (0, None),
+            Some(']') if ! elems.is_empty() => break,
+            _ => {},
+        }
+
+        let (elem, next) = match member(&chars, pos) {
+            Some(m) => m,
+            None => return (0, None),
+        };
+
+        if let BracketElem::Char(from) = elem {
+            if chars.get(next) == Some(&'-') && chars.get(next+1).is_some_and(|c| *c != ']') {
+                if let Some((BracketElem::Char(to), after)) = member(&chars, next+1) {
+                    elems.push(BracketElem::Range(from, to));
+                    pos = after;
+                    continue;
+                }
+            }
+        }
+        elems.push(elem);
+        pos = next;
+    }
+
+    let len = chars[..=pos].iter().map(|c| c.len_utf8()).sum();
+    (len, Some(GlobElem::OneOf(!not, elems)))
+}
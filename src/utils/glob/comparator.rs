@@ -3,7 +3,7 @@
 
 use crate::exit;
 use super::extglob;
-use super::GlobElem;
+use super::{BracketElem, GlobElem};
 
 pub fn shave_word(word: &String, pattern: &Vec<GlobElem>) -> Vec<String> {
     let mut candidates = vec![word.to_string()];
@@ -49,8 +49,12 @@ fn asterisk(cands: &mut Vec<String>) {
     *cands = ans;
 }
 
-fn one_of(cands: &mut Vec<String>, cs: &Vec<char>, not_inv: bool) {
-    cands.retain(|cand| cs.iter().any(|c| cand.starts_with(*c)) == not_inv );
+fn one_of(cands: &mut Vec<String>, elems: &[BracketElem], not_inv: bool) {
+    let matches = |cand: &String| match cand.chars().next() {
+        Some(c) => elems.iter().any(|e| e.matches(c)),
+        None => false,
+    };
+    cands.retain(|cand| matches(cand) == not_inv );
     cands.retain(|cand| cand.len() != 0 );
     let len = |c: &String| c.chars().nth(0).unwrap().len_utf8();
     cands.iter_mut().for_each(|c| {*c = c.split_off(len(c));});
@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use super::{GlobElem, extglob};
+use super::{GlobElem, bracket, extglob};
 
 fn eat_one_char(pattern: &mut String, ans: &mut Vec<GlobElem>) -> bool {
     if pattern.starts_with("*") || pattern.starts_with("?") {
@@ -28,33 +28,12 @@ fn eat_escaped_char(pattern: &mut String, ans: &mut Vec<GlobElem>) -> bool {
 }
 
 fn eat_bracket(pattern: &mut String, ans: &mut Vec<GlobElem>) -> bool {
-    if ! pattern.starts_with("[") {
-        return false;
-    }
-    
-    let not = pattern.starts_with("[^") || pattern.starts_with("[!");
-    let mut len = if not {2} else {1};
-    let mut escaped = false;
-    let mut inner = vec![];
-
-    for c in pattern[len..].chars() {
-        len += c.len_utf8();
-
-        if escaped {
-            inner.push(c); 
-            escaped = false;
-        }else if c == '\\' {
-            escaped = true;
-        }else if c == ']' {
-            let expand_inner = expand_range_representation(&inner);
-            ans.push( GlobElem::OneOf(!not, expand_inner) );
-            *pattern = pattern.split_off(len);
-            return true;
-        }else{
-            inner.push(c);
-        }
+    let (len, bracket) = bracket::scan(pattern);
+    if len > 0 {
+        *pattern = pattern.split_off(len);
+        ans.push(bracket.unwrap());
+        return true;
     }
-
     false
 }
 
@@ -107,61 +86,6 @@ pub fn parse(pattern: &str, extglob: bool) -> Vec<GlobElem> {
     ans
 }
 
-fn expand_range_representation(chars: &Vec<char>) -> Vec<char> {
-    let mut ans = vec![];
-    let mut from = None;
-    let mut hyphen = false;
-
-    for c in chars {
-        if *c == '-' {
-            hyphen = true;
-            continue;
-        }
-
-        if hyphen {
-            if ans.len() > 0 {
-                ans.pop();
-            }
-
-            let mut expand = expand_range(&from, c);
-            ans.append(&mut expand);
-            hyphen = false;
-            continue;
-        }else {
-            ans.push(*c);
-            from = Some(*c);
-        }
-    }
-
-    if hyphen {
-        ans.push('-');
-    }
-
-    ans
-}
-
-fn expand_range(from: &Option<char>, to: &char) -> Vec<char> {
-    if from.is_none() {
-        return vec![*to];
-    }
-
-    let from = from.unwrap();
-
-    let mut ans = vec![];
-
-    if ('0' <= from && from <= *to && *to <= '9')
-    || ('a' <= from && from <= *to && *to <= 'z')
-    || ('A' <= from && from <= *to && *to <= 'Z') {
-        let mut ch = from;
-        while ch <= *to {
-            ans.push(ch);
-            ch = (ch as u8 + 1) as char;
-        }
-
-    }
-    ans
-}
-
 fn consume(remaining: &mut String, cutpos: usize) -> String {
     let cut = remaining[0..cutpos].to_string();
     *remaining = remaining.split_off(cutpos);
@@ -38,6 +38,28 @@ res=$($com <<< 'compgen -W "aaa abc aac" -- aa')
 [ "$res" = "aaa
 aac" ] || err $LINENO
 
+res=$($com <<< 'f () { :; }; fg () { :; }; compgen -A function f')
+[ "$res" = "f
+fg" ] || err $LINENO
+
+res=$($com <<< 'compgen -k -P "<" -S ">" -X "done" do')
+[ "$res" = "<do>" ] || err $LINENO
+
+res=$($com <<< 'compgen -A signal SIGUSR')
+[ "$res" = "SIGUSR1
+SIGUSR2" ] || err $LINENO
+
+res=$($com <<< 'sush_a=(1); sush_b=1; compgen -A arrayvar sush_')
+[ "$res" = "sush_a" ] || err $LINENO
+
+echo '10.0.0.1 sushhost1 sushhost2 # sushhost3' > /tmp/sush_hosts
+res=$($com <<< 'HOSTFILE=/tmp/sush_hosts; compgen -A hostname sush')
+[ "$res" = "sushhost1
+sushhost2" ] || err $LINENO
+
+res=$($com <<< 'compgen -k zzz')
+[ "$?" = "1" ] || err $LINENO
+
 b=$(compgen -f / | wc -l )
 res=$($com <<< 'compgen -f / | wc -l')
 [ "$res" = "$b" ] || err $LINENO
@@ -54,6 +76,40 @@ b=$(cd ; compgen -f . | wc -l )
 res=$($com <<< 'cd ; compgen -f . | wc -l')
 [ "$res" = "$b" ] || err $LINENO
 
+mkdir -p /tmp/$$-compgen && touch /tmp/$$-compgen/--x /tmp/$$-compgen/abc
+res=$($com <<< "cd /tmp/$$-compgen; compgen -f -- --")
+[ "$res" = "--x" ] || err $LINENO
+rm -rf /tmp/$$-compgen
+
+res=$($com <<< 'compgen -W "a --b -- --c" -- --')
+[ "$res" = "--b
+--
+--c" ] || err $LINENO
+
+res=$($com <<< $'complete -o nospace -P "<" -W "a b" -F _f foo; complete -p foo')
+[ "$res" = "complete -o nospace -W 'a b' -P '<' -F _f foo" ] || err $LINENO
+
+res=$($com <<< $'complete -X "!*.txt" -f -A function -D; complete -p')
+[ "$res" = "complete -f -A function -X '!*.txt' -D" ] || err $LINENO
+
+res=$($com <<< 'complete -W a foo; complete -r foo; complete -p foo')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'compopt -o nospace')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'complete -o nosort foo; compopt -o nospace +o nosort foo; complete -p foo')
+[ "$res" = "complete -o nospace foo" ] || err $LINENO
+
+mkdir -p /tmp/sush_completion/completions
+echo 'complete -W x foo' > /tmp/sush_completion/completions/foo
+res=$($com <<< 'BASH_COMPLETION_USER_DIR=/tmp/sush_completion; _completion_loader foo; echo $?; complete -p foo')
+[ "$res" = "124
+complete -W 'x' foo" ] || err $LINENO
+
+res=$($com <<< 'BASH_COMPLETION_USER_DIR=/tmp/sush_completion; XDG_DATA_DIRS=/tmp; _completion_loader bar; echo $?')
+[ "$res" = "1" ] || err $LINENO
+
 res=$($com <<< 'eval "echo a" b')
 [ "$res" = "a b" ] || err $LINENO
 
@@ -154,6 +210,16 @@ echo @(a)')
 [ "$?" == "2" ] || err $LINENO
 [ "$res" == "" ] || err $LINENO
 
+res=$($com <<< 'shopt promptvars')
+[ "$res" == "promptvars      on" ] || err $LINENO
+
+res=$($com <<< 'shopt syntax_highlight; shopt -s syntax_highlight; shopt syntax_highlight')
+[ "$res" == "syntax_highlight	off
+syntax_highlight	on" ] || err $LINENO
+
+res=$($com <<< 'shopt -s autosuggest; shopt autosuggest')
+[ "$res" == "autosuggest     on" ] || err $LINENO
+
 # local
 
 res=$($com -c 'A=1 ; f () { local -a A ; A[1]=123 ; echo ${A[@]} ; } ; f ; echo $A')
@@ -251,6 +317,151 @@ res=$($com <<< 'printf -v a[3] bb cc dd &> /dev/null; echo ${a[@]}')
 res=$($com <<< 'printf -v a[3] bb cc dd &> /dev/null; echo ${a[3]}')
 [ "$res" = "bb" ] || err $LINENO
 
+### history / fc
+
+res=$($com <<< 'history -s echo abc; history -s echo def; history')
+[ "$res" = "    1 echo abc
+    2 echo def" ] || err $LINENO
+
+res=$($com <<< 'history -s echo abc; history -s echo def; history -p "!!:1" "!-2:\$"')
+[ "$res" = "def
+abc" ] || err $LINENO
+
+res=$($com <<< 'history -s ls -l; history -s echo aa/bb.c; history -p "!!" "!1" "!-2" "!l" "!?bb?"')
+[ "$res" = "echo aa/bb.c
+ls -l
+ls -l
+ls -l
+echo aa/bb.c" ] || err $LINENO
+
+res=$($com <<< 'history -s ls -l; history -s echo aa/bb.c; history -p "^a^b" "!!:s/a/b/" "!!:gs/a/b/"')
+[ "$res" = "echo ba/bb.c
+echo ba/bb.c
+echo bb/bb.c" ] || err $LINENO
+
+res=$($com <<< 'history -s ls -l; history -s echo aa/bb.c; history -p "!!:h" "!!:t" "!!:r" "!!:e" "!!:p" "!!:\$:t"')
+[ "$res" = "echo aa
+bb.c
+echo aa/bb
+.c
+echo aa/bb.c
+bb.c" ] || err $LINENO
+
+res=$($com -c 'history -s ls -l; history -p "!9"' 2>&1)
+[ "$res" = "$com: line 1: !9: event not found" ] || err $LINENO
+
+res=$($com -c 'history -s ls -l; history -p "!zz"' 2>&1)
+[ "$res" = "$com: line 1: !zz: event not found" ] || err $LINENO
+
+res=$($com -c 'history -s ls -l; history -p "^x^y"' 2>&1)
+[ "$res" = "$com: line 1: :s^x^y: substitution failed" ] || err $LINENO
+
+res=$($com -c 'history -s ls -l; history -p "!!:5"' 2>&1)
+[ "$res" = "$com: line 1: !!:5: bad word specifier" ] || err $LINENO
+
+res=$($com -c 'history -s ls -l; history -p "!!:0-3"' 2>&1)
+[ "$res" = "$com: line 1: !!:0-3: bad word specifier" ] || err $LINENO
+
+res=$($com <<< 'history -s a; history -s b; history -s c; history -d 1-2; history 1')
+[ "$res" = "    1 c" ] || err $LINENO
+
+res=$($com <<< 'history -s a; history -d 5')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'history -s a; history -s b; history -c; history')
+[ "$res" = "" ] || err $LINENO
+
+res=$($com <<< 'history -s echo a; history -s echo b; fc -l; fc -lnr')
+[ "$res" = "1	 echo a
+2	 echo b
+	 echo b
+	 echo a" ] || err $LINENO
+
+res=$($com <<< 'history -s echo abc; fc -s b=x')
+[ "$res" = "echo axc
+axc" ] || err $LINENO
+
+res=$($com <<< 'history -s echo abc; FCEDIT="sed -i s/abc/def/" fc -1 2> /dev/null')
+[ "$res" = "def" ] || err $LINENO
+
+mkdir -p /tmp/$$-fc
+res=$($com <<< "history -s echo abc; TMPDIR=/tmp/$$-fc FCEDIT='stat -c %a' fc -1 2> /dev/null; ls /tmp/$$-fc")
+[ "$res" = "600
+abc" ] || err $LINENO
+rm -rf /tmp/$$-fc
+
+res=$($com <<< 'HISTTIMEFORMAT="T "; history -s a; history')
+[ "$res" = "    1 T a" ] || err $LINENO
+
+res=$($com <<< 'HISTSIZE=2; history -s a; history -s b; history -s c; history')
+[ "$res" = "    1 b
+    2 c" ] || err $LINENO
+
+res=$($com <<< 'HISTFILE=/tmp/rusty_bash_hist; HISTFILESIZE=2; history -s a; history -s b; history -s c; history -w; cat $HISTFILE')
+[ "$res" = "b
+c" ] || err $LINENO
+
+printf '#100\nold\nnew\n' > /tmp/rusty_bash_hist
+res=$($com <<< 'HISTTIMEFORMAT="%s "; history -r /tmp/rusty_bash_hist; history')
+[ "$res" = "    1 100 old
+    2 ??new" ] || err $LINENO
+
+printf '#100\na\n#200\nb\nc\n' > /tmp/rusty_bash_hist
+res=$($com <<< 'HISTTIMEFORMAT="%s "; HISTSIZE=2; history -r /tmp/rusty_bash_hist; history')
+[ "$res" = "    1 200 b
+    2 ??c" ] || err $LINENO
+
+printf 'a\nb\n' > /tmp/rusty_bash_hist
+res=$($com <<< 'HISTFILE=/tmp/rusty_bash_hist; history -r; echo c >> $HISTFILE; history -n; history')
+[ "$res" = "    1 a
+    2 b
+    3 c" ] || err $LINENO
+
+
+### bind
+
+res=$($com <<< 'bind -q yank')
+[ "$res" = 'yank can be invoked via "\C-y".' ] || err $LINENO
+
+res=$($com <<< $'bind \'"\\C-xy": yank\'; bind -r "\\C-y"; bind -q yank')
+[ "$res" = 'yank can be invoked via "\C-xy".' ] || err $LINENO
+
+res=$($com <<< 'bind -u yank; bind -q yank')
+[ "$?" = "1" ] || err $LINENO
+[ "$res" = 'yank is not bound to any keys.' ] || err $LINENO
+
+res=$($com <<< 'bind -q menu-complete-backward')
+[ "$res" = 'menu-complete-backward can be invoked via "\e[Z".' ] || err $LINENO
+
+res=$($com <<< 'bind "Control-t: kill-word"; bind -p | grep ": kill-word"')
+[ "$res" = '"\C-t": kill-word
+"\ed": kill-word' ] || err $LINENO
+
+res=$($com <<< $'bind -x \'"\\C-t": echo "a"\'; bind \'"\\ez": "ls\\C-m"\'; bind -X; bind -s')
+[ "$res" = '"\C-t": "echo \"a\""
+"\ez": "ls\C-m"' ] || err $LINENO
+
+cat << 'EOF' > /tmp/rusty_bash_inputrc
+# comment
+set show-all-if-ambiguous on
+$if mode=vi
+"\C-y": kill-line
+$else
+"\C-y": backward-word
+$endif
+EOF
+res=$($com <<< 'bind -f /tmp/rusty_bash_inputrc; bind -v | grep ambiguous; bind -q backward-word')
+[ "$res" = 'set show-all-if-ambiguous on
+backward-word can be invoked via "\C-y", "\eb".' ] || err $LINENO
+
+res=$($com <<< $'bind \'"\\C-t": no-such-function\'')
+[ "$?" = "1" ] || err $LINENO
+
+printf '"\\C-x\n' > /tmp/rusty_bash_inputrc
+res=$($com <<< 'bind -f /tmp/rusty_bash_inputrc 2>&1; echo ok')
+[ "$res" = "$com: line 1: bind: /tmp/rusty_bash_inputrc: line 1: \"\\C-x: no closing \`\"' in key binding
+ok" ] || err $LINENO
+rm -f /tmp/rusty_bash_inputrc
 
 echo $0 >> ./ok
 
@@ -606,6 +606,34 @@ res=$($com <<< 'shopt -u extglob; case baa in @(a|b)aa) echo OK ;; *) echo NG ;;
 res=$($com <<< 'shopt -u extglob; case 山小小小田 in !(山)田) echo マッチ ;; *) echo マッチせず ;; esac')
 [ "$res" = "マッチせず" ] || err $LINENO
 
+res=$($com <<< 'shopt -s nocasematch; case HeLLo in hello) echo OK ;; *) echo NG ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nocasematch; case FOO in [a-f]@(x|o)O) echo OK ;; *) echo NG ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nocasematch; case Q in [!q]) echo NG ;; *) echo OK ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nocasematch; case a in [[:upper:]]) echo NG ;; [B-Z[:digit:]]) echo NG ;; [A]) echo OK ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< 'case HeLLo in hello) echo NG ;; *) echo OK ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nocasematch; [[ ÄBC == äb? ]] && [[ ! ABC != abc ]]')
+[ "$?" = "0" ] || err $LINENO
+
+res=$($com <<< 'for w in a 5 " " "]" "-" ; do case "$w" in [[:alpha:]]) echo a ;; [[:digit:]]) echo d ;; [[:space:]]) echo s ;; []x]) echo b ;; [^]a-z]) echo n ;; esac ; done')
+[ "$res" = "a
+d
+s
+b
+n" ] || err $LINENO
+
+res=$($com <<< 'case é in [[=e=]]) echo OK ;; *) echo NG ;; esac')
+[ "$res" = "OK" ] || err $LINENO
+
 ### (( )) TEST ###
 
 res=$($com <<< '(( 0 ))')
@@ -978,6 +1006,30 @@ res=$($com -c 'A=あいう ; [[ A =~ あ ]]')
 res=$($com -c 'A=あいう ; [[ $A =~ * ]]')
 [ "$?" = "2" ] || err $LINENO
 
+res=$($com -c '[[ abcd =~ (a|ab)(c|bcd)(d*) ]]; echo ${BASH_REMATCH[@]}')
+[ "$res" = "abcd a bcd" ] || err $LINENO
+
+res=$($com -c '[[ xyz =~ (y|yz) ]]; echo ${BASH_REMATCH[0]}')
+[ "$res" = "yz" ] || err $LINENO
+
+res=$($com -c '[[ "a]b" =~ []a]+ ]]; echo ${BASH_REMATCH[0]}')
+[ "$res" = "a]" ] || err $LINENO
+
+res=$($com -c '[[ x12 =~ [[:digit:]]{2}$ ]]; echo ${BASH_REMATCH[0]}')
+[ "$res" = "12" ] || err $LINENO
+
+res=$($com -c '[[ ab =~ b ]]; [[ ab =~ c ]]; echo ${BASH_REMATCH[0]}x')
+[ "$res" = "x" ] || err $LINENO
+
+res=$($com -c 'shopt -s nocasematch; [[ ABC =~ b(c) ]]; echo ${BASH_REMATCH[@]}')
+[ "$res" = "BC C" ] || err $LINENO
+
+$com -c 'x=; [[ $x =~ a ]]'
+[ "$?" = "1" ] || err $LINENO
+
+$com -c '[[ $unset =~ ^$ ]]'
+[ "$?" = "0" ] || err $LINENO
+
 # and or 
 
 res=$($com -c '[[ -a /etc/passwd && -a /etc/passwd ]]')
@@ -58,6 +58,51 @@ res=$($com <<< 'echo @(あ|{い,う,})')
 res=$($com <<< 'echo \/e\tc/* | grep -F "*"')
 [ $? -eq 1 ] || err $LINENO
 
+### globstar ###
+
+rm -rf /tmp/sush_globstar
+mkdir -p /tmp/sush_globstar/a/b/c /tmp/sush_globstar/.h
+touch /tmp/sush_globstar/a/x /tmp/sush_globstar/a/b/c/x /tmp/sush_globstar/.h/x
+ln -s .. /tmp/sush_globstar/a/b/loop
+
+res=$($com <<< 'cd /tmp/sush_globstar; shopt -s globstar; echo **')
+[ "$res" = "a a/b a/b/c a/b/c/x a/b/loop a/x" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globstar; shopt -s globstar; echo a/**/')
+[ "$res" = "a/ a/b/ a/b/c/ a/b/loop/" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globstar; shopt -s globstar; for f in **/x ; do echo $f ; done')
+[ "$res" = "a/b/c/x
+a/x" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globstar; echo **/x')
+[ "$res" = "a/x" ] || err $LINENO
+
+### glob options ###
+
+rm -rf /tmp/sush_globopt
+mkdir -p /tmp/sush_globopt/empty
+touch /tmp/sush_globopt/{a.txt,B.TXT,.hid,c.log}
+
+res=$($com <<< 'cd /tmp/sush_globopt; shopt -s nullglob; echo x *.zz y; for f in empty/* ; do echo in ; done')
+[ "$res" = "x y" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globopt; shopt -s failglob; echo *.zz')
+[ "$?" = "1" ] || err $LINENO
+[ "$res" = "" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globopt; shopt -s dotglob; echo *')
+[ "$res" = ".hid B.TXT a.txt c.log empty" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globopt; shopt -s nocaseglob; echo *.txt')
+[ "$res" = "B.TXT a.txt" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globopt; GLOBIGNORE="*.txt:c.*"; echo *')
+[ "$res" = ".hid B.TXT empty" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/sush_globopt; shopt -s nullglob; [ -d empty ] && echo "a@b" hi!')
+[ "$res" = "a@b hi!" ] || err $LINENO
+
 if [ "$(uname)" = Linux ] ; then
 	res=$($com <<< 'touch /tmp/2 ; echo /tmp/[1-5]' | grep 2)
 	[ "$?" == "0" ] || err $LINENO
@@ -61,11 +61,229 @@ res=$($com <<< 'set -o pipefail; ls aaaa | false | true')
 res=$($com <<< 'set -o pipefail; set -e; false | true ; echo NG')
 [ "$res" == "" ] || err $LINENO
 
+res=$($com <<< 'set -e ; if false ; then echo NG ; fi ; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; false && true ; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; true && false ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e ; ! true ; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; ! { false ; echo a ; } ; echo OK')
+[ "$res" == "a
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; { false && true ; } ; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; ( false && true ) ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e ; f () { false && true ; } ; f ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e ; f () { false ; echo a ; } ; if f ; then echo b ; fi ; f || echo c ; echo OK')
+[ "$res" == "a
+b
+a
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; if true ; then while false ; do : ; done ; false ; echo NG ; fi')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e ; if { while false ; do : ; done ; false ; echo a ; } ; then echo OK ; fi')
+[ "$res" == "a
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; false ; x=1 ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e ; x=$(false) ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e ; echo $(false ; echo a) ; echo OK')
+[ "$res" == "a
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e ; shopt -s inherit_errexit ; echo $(false ; echo NG) ; echo OK')
+[ "$res" == "
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -E ; echo $-')
+[[ "$res" =~ E ]] || err $LINENO
+
+### -u
+
+res=$($com <<< 'set -u ; echo $x ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com -c 'set -u ; echo ${x}')
+[ "$?" == "127" ] || err $LINENO
+
+res=$($com -c 'set -u ; echo $x' 2>&1)
+[ "$res" == "$com: line 1: x: unbound variable" ] || err $LINENO
+
+res=$($com -c 'set -u ; echo $2' 2>&1)
+[ "$res" == "$com: line 1: \$2: unbound variable" ] || err $LINENO
+
+res=$($com -c 'set -u ; a=(1) ; echo ${a[3]}' 2>&1)
+[ "$res" == "$com: line 1: a[3]: unbound variable" ] || err $LINENO
+
+res=$($com -c 'set -u ; f(){ local y; echo $y; } ; f' 2>&1)
+[ "$res" == "$com: line 1: y: unbound variable" ] || err $LINENO
+
+res=$($com -c 'y=g ; f(){ local y; echo ${y-unset}; y[1]=a; echo ${y[1]}; } ; f ; echo $y')
+[ "$res" == "unset
+a
+g" ] || err $LINENO
+
+res=$($com <<< 'set -u ; echo ${#x} ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -u ; echo $(( y + 1 )) ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -u ; y=$x ; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -u ; echo $@ $* $# ${x-a} ${x:-b} ${x+c}${x:+d} ${2-e} ; echo OK')
+[ "$res" == "0 a b e
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -u ; a=() ; echo ${a[@]} ${#a[@]} ${b[@]} ; x= ; echo "[$x]"')
+[ "$res" == "0
+[]" ] || err $LINENO
+
+res=$($com <<< 'set -u ; set +u ; echo $x OK')
+[ "$res" == "OK" ] || err $LINENO
+
+### -o
+
+res=$($com <<< 'set -o errexit -o nounset ; echo $- ; set +o errexit ; echo $-')
+[ "$res" == "euB
+uB" ] || err $LINENO
+
+res=$($com <<< 'set -ue ; set -o | grep -E "^(errexit|nounset|noglob) "')
+[ "$res" == "errexit         on
+noglob          off
+nounset         on" ] || err $LINENO
+
+res=$($com <<< 'set -C ; set +o | grep -E "noclobber|allexport"')
+[ "$res" == "set +o allexport
+set -o noclobber" ] || err $LINENO
+
+res=$($com <<< 'set -T ; echo $- ; set +T ; set -o functrace ; set +o | grep functrace')
+[ "$res" == "BT
+set -o functrace" ] || err $LINENO
+
+res=$($com <<< 'set -o nounset ; [[ -o nounset ]] && [[ $- == *u* ]] && echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -o posix ; [[ -o posix ]] && echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -o foo')
+[ "$?" == "2" ] || err $LINENO
+
+res=$($com <<< 'set -e a b ; echo $- $@')
+[ "$res" == "eB a b" ] || err $LINENO
+
+res=$($com <<< 'set -f ; echo /* ; set +f ; set -o noglob ; echo /*')
+[ "$res" == "/*
+/*" ] || err $LINENO
+
+res=$($com <<< 'set -a ; SUSH_ALLEXPORT=1 ; printenv SUSH_ALLEXPORT')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'set -t
+echo a
+echo NG')
+[ "$res" == "a" ] || err $LINENO
+
 ### -B
 
 res=$($com <<< 'set +B; echo {a,b}')
 [ "$res" == "{a,b}" ] || err $LINENO
 
+### -H
+
+res=$($com <<< 'set -H; echo $-; set +H; echo $-')
+[ "$res" == "BH
+B" ] || err $LINENO
+
+res=$($com <<< 'set -H; echo !!')
+[ "$res" == "!!" ] || err $LINENO
 
 
+### -n
+
+res=$($com <<< 'set -n; echo NG
+echo NG')
+[ "$?" == "0" ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
+res=$($com -c 'set -n; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+cat << 'EOF' > /tmp/$$-n
+echo NG
+if true; then
+	echo NG
+fi fi
+echo a )
+EOF
+res=$($com -n /tmp/$$-n 2>&1)
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "/tmp/$$-n: line 4: syntax error near unexpected token \`fi'
+fi fi
+   ^^
+/tmp/$$-n: line 5: syntax error near unexpected token \`)'
+echo a )
+       ^" ] || err $LINENO
+
+printf 'echo NG\n\techo (\n' > /tmp/$$-n
+res=$($com -n /tmp/$$-n 2>&1)
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "/tmp/$$-n: line 2: syntax error near unexpected token \`('
+	echo (
+	     ^" ] || err $LINENO
+
+printf 'while true; do\n  echo NG\n' > /tmp/$$-n
+res=$($com -n /tmp/$$-n 2>&1)
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "/tmp/$$-n: line 1: unexpected EOF while looking for \`done' to close \`while'
+while true; do
+^^^^^" ] || err $LINENO
+
+printf 'echo NG\n' > /tmp/$$-n
+res=$($com -n /tmp/$$-n 2>&1)
+[ "$?" == "0" ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
+rm -f /tmp/$$-n
+
+### vi/emacs
+
+res=$($com <<< 'set -o vi; set -o | grep -E "^(vi|emacs) "')
+[ "$res" == "emacs           off
+vi              on" ] || err $LINENO
+
+res=$($com <<< 'set -o vi; bind -v | grep editing-mode; set -o emacs; bind -v | grep editing-mode')
+[ "$res" == "set editing-mode vi
+set editing-mode emacs" ] || err $LINENO
+
+res=$($com <<< 'bind -m vi-command -q vi-undo; bind -q vi-undo')
+[ "$res" == 'vi-undo can be invoked via "u".
+vi-undo is not bound to any keys.' ] || err $LINENO
+
+res=$($com <<< 'bind "set editing-mode vi"; set -o | grep -E "^vi "')
+[ "$res" == "vi              on" ] || err $LINENO
+
+res=$($com <<< 'bind -m vim -q vi-undo')
+[ "$?" == "1" ] || err $LINENO
+
 echo $0 >> ./ok
@@ -50,6 +50,9 @@ res=$($com <<< 'echo ${A:-abc}' )
 res=$($com <<< 'echo ${A:-abc}; echo $A' )
 [ "$res" = "abc" ] || err $LINENO
 
+res=$($com <<< 'A=x ; B= ; echo ${A:-abc} ${A-def} ${C-ghi} [${B-jkl}]' )
+[ "$res" = "x x ghi []" ] || err $LINENO
+
 res=$($com <<< 'echo ${A:=abc}; echo $A' )
 [ "$res" = "abc
 abc" ] || err $LINENO
@@ -172,6 +175,9 @@ res=$($com <<< 'A="あいう うえお"; echo ${A#*う う}' )
 res=$($com <<< 'A="[["; echo ${A%%[[(]}' )
 [ "$res" = "[" ] || err $LINENO
 
+res=$($com <<< 'A=abc123; echo ${A#[[:alpha:]]} ${A%%[[:digit:]]*} ${A/[[:digit:]]/X}' )
+[ "$res" = "bc123 abc abcX23" ] || err $LINENO
+
 # replace
 
 res=$($com -c 'A="あいう うえお"; echo ${A/あ/}' )
@@ -660,4 +666,97 @@ res=$($com <<< 'A=( a b ); A[]=1')
 res=$($com <<< 'echo ]')
 [ "$res" == "]" ] || err $LINENO
 
+### PROMPT ###
+
+root_mark='$'
+[ "$(id -u)" = 0 ] && root_mark='#'
+
+res=$($com <<< 'cd /tmp; PS1='"'"'\u:\h:\w:\W:\$'"'"'; echo "${PS1@P}"')
+[ "$res" == "$(id -un):$(uname -n | cut -d. -f1):/tmp:tmp:$root_mark" ] || err $LINENO
+
+res=$($com <<< 'cd; PS1='"'"'\w:\W'"'"'; echo "${PS1@P}"')
+[ "$res" == "~:~" ] || err $LINENO
+
+res=$($com <<< 'PS1='"'"'\D{%Y}:\[\e[0m\]:\\:\101'"'"'; echo "${PS1@P}"' | cat -v)
+[ "$res" == "$(date +%Y):^[[0m:\\:A" ] || err $LINENO
+
+res=$($com <<< 'PS1='"'"'\t \d'"'"'; echo "${PS1@P}"')
+[[ "$res" =~ ^[0-2][0-9]:[0-5][0-9]:[0-6][0-9]\ [A-Z][a-z]{2}\ [A-Z][a-z]{2}\ [0-3][0-9]$ ]] || err $LINENO
+
+res=$($com <<< 'PS1='"'"'\j \! \#'"'"'; echo "${PS1@P}"; sleep 1 & history -s a; echo "${PS1@P}"')
+[ "$res" == "0 1 1
+1 2 1" ] || err $LINENO
+
+res=$($com <<< 'a=A; PS1='"'"'$a \$'"'"'; echo "${PS1@P}"; shopt -u promptvars; echo "${PS1@P}"')
+[ "$res" == "A $root_mark
+\$a $root_mark" ] || err $LINENO
+
+res=$($com <<< 'a=A; PS1='"'"'$a'"'"'; shopt -u promptvars; shopt -s promptvars; echo "${PS1@P}"')
+[ "$res" == "A" ] || err $LINENO
+
+
+### INTERACTIVE ###
+
+# types each argument as a line on a terminal; the first key may be eaten by the cursor position query
+interactive () {
+	(sleep 0.5; for line in "$@"; do printf ' %s' "$line"; sleep 0.3; done; printf ' exit\r'; sleep 0.3) |
+	HOME=/tmp/$$-home script -qfc "$com" /dev/null
+}
+mkdir -p /tmp/$$-home
+
+res=$(interactive $'PROMPT_COMMAND=\'echo pc\'; PS0=\'ps0\\n\'\r' $'echo hi\r' |
+      sed 's/\x1b\[[0-9;]*[A-Za-z]//g' | tr -d '\r' | grep -av "Rusty\|🍣")
+[ "$res" == "pc
+ps0
+hi
+pc
+ps0
+exit" ] || err $LINENO
+
+res=$(interactive $'shopt -s syntax_highlight\r' $'if true; then nosuchcmd "x" $HOME > /dev/null; fi # c\x03' $'echo \'ab\x03')
+[[ "$res" == *$'\e[01;33mif\e[00m \e[01;32mtrue\e[00m; \e[01;33mthen\e[00m \e[31mnosuchcmd\e[00m \e[33m"x"\e[00m \e[35m$HOME\e[00m \e[01m>\e[00m /dev/null; \e[01;33mfi\e[00m \e[90m# c\e[00m'* ]] || err $LINENO
+[[ "$res" == *$'echo \e[41m\'ab\e[00m'* ]] || err $LINENO
+
+res=$(interactive $'shopt -s autosuggest; history -s \'echo abc def ghi\'\r' $'history -s \'echo abx\'\r' \
+      $'echo ab\x06\r' $'echo abc\x06\r' $'echo abc\ef\r' $'echo zz\x06\r' |
+      sed 's/\x1b\[[0-9;]*[A-Za-z]//g' | tr -d '\r' | grep -av "Rusty\|🍣")
+[ "$res" == "abx
+abc def ghi
+abc def
+zz
+exit" ] || err $LINENO
+
+res=$(interactive $'set -o vi\r' $'echo abd\ehiX\r' $'echo ac\ehab\r' $'echo abc def\ebdwAghi\r' \
+      $'echo abc def\e0wcwxyz\r' $'echo abc\e0wlx$ad\r' $'\e/xyz\r\r' $'\ekk\r' |
+      sed 's/\x1b\[[0-9;]*[A-Za-z]//g' | tr -d '\r' | grep -av "Rusty\|🍣")
+[ "$res" == "aXbd
+abc
+abc ghi
+xyz def
+acd
+xyz def
+acd
+exit" ] || err $LINENO
+
+printf 'echo aa1\necho ab2\necho ab3\necho ac4\n' > /tmp/$$-home/.sush_history
+res=$(interactive $'\x12ab\r' $'\x12ab\x12\r' $'\x12ab\x12\x13\r' $'echo zz\x12ac\x07\r' |
+      sed 's/\x1b\[[0-9;]*[A-Za-z]//g' | tr -d '\r' | grep -av "Rusty\|🍣")
+[ "$res" == "ab3
+ab2
+ab2
+zz
+exit" ] || err $LINENO
+
+res=$(interactive $'complete -W \'ab1 ab2 ab3\' echo\r' $'echo a\t\t\t\r\r' $'echo a\t\t\t\t\r\r' \
+      $'echo a\t\t\e[Z\r\r' $'echo a\t\t\e[Z\e[Z\r\r' $'echo a\t\t\t\x07\r' |
+      sed 's/\x1b\[[0-9;]*[A-Za-z]//g' | tr -d '\r' | grep -av "Rusty\|🍣\|^ *$")
+[ "$res" == "ab1
+ab2
+ab3
+ab2
+ab
+exit" ] || err $LINENO
+
+rm -rf /tmp/$$-home
+
 echo $0 >> ./ok
@@ -57,6 +57,50 @@ res=$($com <<< 'B=ccc; declare -A A; A[aaa]=bbb ;A[ccc]=ddd ; echo ${A[$B]}')
 res=$($com <<< 'f(){ g () { echo ${FUNCNAME[@]} ;} ; g ;} ; f')
 [ "$res" == "g f" ] || err $LINENO
 
+### CALL STACK ###
+
+cat << 'EOF' > /tmp/sush_caller_lib
+g () { echo ${BASH_SOURCE[@]} ${BASH_LINENO[@]} ; caller 0 ; caller 1 ; caller 2 ; echo $? ; }
+EOF
+cat << 'EOF' > /tmp/sush_caller
+source /tmp/sush_caller_lib
+f () {
+  g
+}
+f
+EOF
+
+res=$($com /tmp/sush_caller)
+[ "$res" = "/tmp/sush_caller_lib /tmp/sush_caller /tmp/sush_caller 3 5 0
+3 f /tmp/sush_caller
+5 main /tmp/sush_caller
+1" ] || err $LINENO
+
+res=$($com <<< 'f () { echo $BASH_COMMAND ; } ; f a   ')
+[ "$res" = 'echo $BASH_COMMAND' ] || err $LINENO
+
+res=$($com <<< 'shopt -s extdebug; f () { echo ${BASH_ARGV[@]} ${BASH_ARGC[@]} ; } ; f a b')
+[ "$res" = "b a 2" ] || err $LINENO
+
+### COMP_WORDBREAKS ###
+
+res=$($com <<< 'echo -n "$COMP_WORDBREAKS"')
+[ "$res" == $' \t\n"\'><=;|&(:' ] || err $LINENO
+
+### COMP_LINE, COMP_POINT, COMP_CWORD, COMP_WORDS ###
+
+# types the lines on a terminal; the first key of each line may be eaten by the cursor position query
+mkdir -p /tmp/$$-home
+(sleep 0.5
+ printf ' %s\r' 'f () { echo "$COMP_LINE|$COMP_POINT|$COMP_CWORD|${COMP_WORDS[*]}|$1|$2|$3" >> '"/tmp/$$-comp ; } ; complete -F f cmd"
+ sleep 0.3; printf ' %s' $'cmd ab cd\x02\t\x03'
+ sleep 0.3; printf ' %s' $'cmd ab \t\x03'
+ sleep 0.3; printf ' exit\r'; sleep 0.3) | HOME=/tmp/$$-home script -qfc "$com" /dev/null > /dev/null
+res=$(cat /tmp/$$-comp)
+[ "$res" == "cmd ab cd|8|2|cmd ab cd|cmd|c|ab
+cmd ab |7|2|cmd ab |cmd||ab" ] || err $LINENO
+rm -rf /tmp/$$-home /tmp/$$-comp
+
 ### INDIRECT EXPANSION ###
 
 res=$($com -c 'A=B; B=100; echo ${!A}')
@@ -139,4 +139,32 @@ if [ "$(uname)" = "Linux" ] ; then
 fi
 
 
+# noclobber
+
+rm -f /tmp/$$-noclobber
+res=$($com <<< "set -C ; echo a > /tmp/$$-noclobber ; echo b > /tmp/$$-noclobber ; cat /tmp/$$-noclobber" 2>&1)
+[ "$res" == "sush: /tmp/$$-noclobber: cannot overwrite existing file
+a" ] || err $LINENO
+
+res=$($com <<< "set -o noclobber ; echo b &> /tmp/$$-noclobber ; echo \$?")
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< "set -C ; echo c >| /tmp/$$-noclobber ; echo d >> /tmp/$$-noclobber ; cat /tmp/$$-noclobber")
+[ "$res" == "c
+d" ] || err $LINENO
+
+res=$($com <<< "set -C ; echo e > /dev/null && echo OK")
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< "set -C ; set +C ; echo f > /tmp/$$-noclobber ; cat /tmp/$$-noclobber")
+[ "$res" == "f" ] || err $LINENO
+
+rm -f /tmp/$$-noclobber-link
+ln -s /tmp/$$-noclobber-none /tmp/$$-noclobber-link
+res=$($com <<< "set -C ; echo g > /tmp/$$-noclobber-link" 2>&1)
+[ "$res" == "sush: /tmp/$$-noclobber-link: cannot overwrite existing file" ] || err $LINENO
+rm -f /tmp/$$-noclobber-link
+rm -f /tmp/$$-noclobber
+
+
 echo $0 >> ./ok
@@ -59,4 +59,62 @@ chmod +x $tmp-script
 res=$($tmp-script)
 [ "$res" = "4 4" ] || err $LINENO
 
+
+cat << 'EOF' > $tmp-script
+echo a
+if true; then
+	echo 'b
+EOF
+res=$($com $tmp-script 2>&1)
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "a
+$tmp-script: line 3: unexpected EOF while looking for matching \`''
+	echo 'b
+	     ^" ] || err $LINENO
+
+printf 'case a in\n  a) echo "${x}"\n' > $tmp-script
+res=$($com $tmp-script 2>&1)
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "$tmp-script: line 1: unexpected EOF while looking for \`esac' to close \`case'
+case a in
+^^^^" ] || err $LINENO
+
+printf 'f () {\n  echo $(ls\n' > $tmp-script
+res=$($com $tmp-script 2>&1)
+[ "$res" == "$tmp-script: line 2: unexpected EOF while looking for \`)' to close \`\$('
+  echo \$(ls
+       ^^" ] || err $LINENO
+
+printf 'f () {\n  echo\n' > $tmp-script
+res=$($com $tmp-script 2>&1)
+[ "$res" == "$tmp-script: line 1: unexpected EOF while looking for \`}' to close \`{'
+f () {
+     ^" ] || err $LINENO
+
+printf 'echo a >\n' > $tmp-script
+res=$($com $tmp-script 2>&1)
+[ "$?" == "2" ] || err $LINENO
+[ "$res" == "$tmp-script: line 1: syntax error near unexpected token \`newline'
+echo a >
+        ^" ] || err $LINENO
+
+printf 'echo a > | cat\n' > $tmp-script
+res=$($com $tmp-script 2>&1)
+[ "$res" == "$tmp-script: line 1: syntax error near unexpected token \`|'
+echo a > | cat
+         ^" ] || err $LINENO
+
+res=$($com <<< 'echo a; done' 2>&1)
+[ "$res" == "$com: line 1: syntax error near unexpected token \`done'
+echo a; done
+        ^^^^" ] || err $LINENO
+
+printf 'echo $(( 1 +\n' > $tmp-script
+res=$($com $tmp-script 2>&1)
+[ "$res" == "$tmp-script: line 1: unexpected EOF while looking for \`))' to close \`\$(('
+echo \$(( 1 +
+     ^^^" ] || err $LINENO
+
+rm -f $tmp-script
+
 echo $0 >> ./ok